@@ -1,13 +1,20 @@
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    num::NonZeroU32,
+    sync::Arc,
     task::Poll,
     time::SystemTime,
 };
 
-use futures::FutureExt;
+use futures::{
+    channel::{mpsc, oneshot},
+    future::{self, BoxFuture},
+    stream::{FuturesOrdered, FuturesUnordered},
+    FutureExt, StreamExt,
+};
 use futures_timer::Delay;
 use libp2p::{
-    identity::Keypair,
+    identity::{Keypair, PublicKey},
     swarm::{
         behaviour::ConnectionEstablished, ConnectionClosed, FromSwarm, NetworkBehaviour, ToSwarm,
     },
@@ -17,31 +24,84 @@ use prometheus_client::registry::Registry;
 use quick_protobuf::{MessageWrite, Writer};
 
 use crate::{
-    config::Config,
-    dog::{Controller, Route, Router},
-    error::PublishError,
+    config::{Config, DuplicateFilterMode, ValidationMode},
+    dog::{Controller, DisableRouteRejection, Misbehaviour, ResetRouteRejection, Route, Router},
+    duplicate_filter::{DuplicateFilter, RotatingBloomFilter},
+    error::{PublishError, ValidationError},
     handler::{Handler, HandlerEvent, HandlerIn},
+    iblt::{self, Iblt},
+    known_transactions::KnownTransactions,
     metrics::Metrics,
+    outcome::{PublishOutcome, PublishOutcomes},
     protocol::SIGNING_PREFIX,
+    published::{PublishBuffer, PublishedTransactions},
+    publisher::{DogPublisher, PublishRequest},
+    rate_limit::PeerRateLimiter,
     rpc::Sender,
     rpc_proto::proto,
+    seqno_window::SequenceNumberWindows,
+    signer::TransactionSigner,
+    store::TransactionStore,
+    sync::SyncState,
     time_cache::DuplicateCache,
-    transform::{DataTransform, IdentityTransform},
+    transform::{DataTransform, IdentityTransform, InboundContext, OutboundContext},
     types::{
-        ControlAction, HaveTx, PeerConnections, RawTransaction, ResetRoute, RpcOut, Transaction,
-        TransactionId,
+        ControlAction, HaveTx, PeerConnections, PublishOptions, RawTransaction, Reconcile,
+        ResetRoute, RpcOut, SyncRequest, SyncSummary, Transaction, TransactionId,
     },
 };
 
 /// Determines if published transaction should be signed or not.
+///
+/// Without signing, a number of privacy preserving modes can be selected.
+///
+/// NOTE: The default validation settings are to require signatures. The [`ValidationMode`]
+/// should be updated in the [`Config`] to allow for unsigned transactions.
 #[derive(Debug)]
 pub enum TransactionAuthenticity {
     /// Transaction signing is enabled. The author will be the owner of the key and
     /// the sequence number will be linearly increasing.
     Signed(Keypair),
+    /// Transaction signing is enabled and delegated to a [`TransactionSigner`], so the private
+    /// key does not need to live in the process. The author will be derived from the signer's
+    /// public key and the sequence number will be linearly increasing.
+    ///
+    /// NOTE: [`Behaviour::publish`] fails with [`PublishError::AsyncSigner`], transactions must be
+    /// published with a [`DogPublisher`] so that the signer is awaited without blocking the swarm
+    /// task.
+    Custom(Box<dyn TransactionSigner>),
     /// Transaction signing is disabled. The specified [`PeerId`] will be used as the author
     /// of all published transactions. The sequence number will be linearly increasing.
     Author(PeerId),
+    /// Transaction signing is disabled.
+    ///
+    /// A random [`PeerId`] will be used when publishing each transaction. The sequence number
+    /// will be randomized.
+    RandomAuthor,
+    /// Transaction signing is disabled.
+    ///
+    /// The author of the transaction and the sequence number are excluded from the transaction.
+    ///
+    /// NOTE: Excluding these fields may make these transactions invalid by other nodes who
+    /// enforce validation of these fields. See [`ValidationMode`] in the [`Config`] for how to
+    /// customise this. A custom `transaction_id` function will need to be set to prevent all
+    /// transactions from a peer being filtered as duplicates.
+    Anonymous,
+}
+
+impl TransactionAuthenticity {
+    /// Returns true if signing is enabled.
+    pub fn is_signing(&self) -> bool {
+        matches!(
+            self,
+            TransactionAuthenticity::Signed(_) | TransactionAuthenticity::Custom(_)
+        )
+    }
+
+    /// Returns true if published transactions carry no author nor sequence number.
+    pub fn is_anonymous(&self) -> bool {
+        matches!(self, TransactionAuthenticity::Anonymous)
+    }
 }
 
 /// Event that can be emitted by the dog behaviour.
@@ -55,15 +115,55 @@ pub enum Event {
         transaction_id: TransactionId,
         /// The transaction itself.
         transaction: Transaction,
+        /// The number of times the transaction has been forwarded before reaching the local node,
+        /// 0 if it has been received from its author or published by the local node.
+        hops: u32,
     },
     /// The router's routes have been updated.
     RoutingUpdated {
         /// The current disabled routes.
         disabled_routes: Vec<Route>,
     },
+    /// A transaction published by the local node has been sent or dropped by every peer it was
+    /// queued to.
+    PublishOutcome {
+        /// The [`TransactionId`] of the published transaction.
+        transaction_id: TransactionId,
+        /// The peers the transaction has been written to.
+        sent_to: Vec<PeerId>,
+        /// The peers the transaction could not be sent to, either because their queue was full,
+        /// the transaction timed out in their queue or they disconnected.
+        dropped_at: Vec<PeerId>,
+    },
+    /// Transactions or control messages received from a peer have been dropped because the peer
+    /// exceeded its rate limits. The application may penalize the peer, e.g. by disconnecting it.
+    PeerRateLimited {
+        /// The peer that exceeded its rate limits.
+        peer_id: PeerId,
+        /// The number of transactions dropped.
+        dropped_transactions: usize,
+        /// The number of control messages dropped.
+        dropped_control_messages: usize,
+    },
+    /// A transaction published while no DOG peer was connected has been dropped from the pending
+    /// publish buffer, either because it expired or because the buffer was full.
+    PublishExpired {
+        /// The [`TransactionId`] of the published transaction.
+        transaction_id: TransactionId,
+    },
+    /// A peer sent control messages that may be an attempt to manipulate the routing. The
+    /// offending messages have been ignored. The application may penalize the peer, e.g. by
+    /// disconnecting it.
+    PeerMisbehaviour {
+        /// The misbehaving peer.
+        peer_id: PeerId,
+        /// The observed misbehaviour.
+        misbehaviour: Misbehaviour,
+    },
 }
 
 // A data structure for storing configuration for publishing transactions.
+#[allow(clippy::large_enum_variant)]
 enum PublishConfig {
     Signing {
         keypair: Keypair,
@@ -71,10 +171,24 @@ enum PublishConfig {
         inline_key: Option<Vec<u8>>,
         last_seqno: SequenceNumber,
     },
+    Custom {
+        signer: Arc<dyn TransactionSigner>,
+        author: PeerId,
+        inline_key: Option<Vec<u8>>,
+        last_seqno: SequenceNumber,
+    },
     Author {
         author: PeerId,
         last_seqno: SequenceNumber,
     },
+    // The `local_id` is only used to identify our own transactions in the router and the
+    // duplicate cache. It is never sent on the wire.
+    RandomAuthor {
+        local_id: PeerId,
+    },
+    Anonymous {
+        local_id: PeerId,
+    },
 }
 
 /// A strictly linearly increasing sequence number.
@@ -107,7 +221,10 @@ impl PublishConfig {
     pub(crate) fn get_own_id(&self) -> PeerId {
         match self {
             Self::Signing { author, .. } => *author,
+            Self::Custom { author, .. } => *author,
             Self::Author { author, .. } => *author,
+            Self::RandomAuthor { local_id } => *local_id,
+            Self::Anonymous { local_id } => *local_id,
         }
     }
 }
@@ -117,21 +234,21 @@ impl From<TransactionAuthenticity> for PublishConfig {
         match authenticity {
             TransactionAuthenticity::Signed(keypair) => {
                 let public_key = keypair.public();
-                let key_enc = public_key.encode_protobuf();
-                let key = if key_enc.len() <= 42 {
-                    // The public key can be inlined in [`rpc_proto::proto::Transaction::from`], so we
-                    // don't include it specifically in the
-                    // [`rpc_proto::proto::Transaction::key`] field.
-                    None
-                } else {
-                    // Include the protobuf encoding of the public key in the message.
-                    Some(key_enc)
-                };
 
                 PublishConfig::Signing {
                     keypair,
                     author: public_key.to_peer_id(),
-                    inline_key: key,
+                    inline_key: inline_key(&public_key),
+                    last_seqno: SequenceNumber::new(),
+                }
+            }
+            TransactionAuthenticity::Custom(signer) => {
+                let public_key = signer.public_key();
+
+                PublishConfig::Custom {
+                    signer: Arc::from(signer),
+                    author: public_key.to_peer_id(),
+                    inline_key: inline_key(&public_key),
                     last_seqno: SequenceNumber::new(),
                 }
             }
@@ -139,10 +256,157 @@ impl From<TransactionAuthenticity> for PublishConfig {
                 author,
                 last_seqno: SequenceNumber::new(),
             },
+            TransactionAuthenticity::RandomAuthor => PublishConfig::RandomAuthor {
+                local_id: PeerId::random(),
+            },
+            TransactionAuthenticity::Anonymous => PublishConfig::Anonymous {
+                local_id: PeerId::random(),
+            },
         }
     }
 }
 
+/// Returns the protobuf encoding of the public key if it cannot be inlined in the author
+/// [`PeerId`].
+fn inline_key(public_key: &PublicKey) -> Option<Vec<u8>> {
+    let key_enc = public_key.encode_protobuf();
+    if key_enc.len() <= 42 {
+        // The public key can be inlined in [`rpc_proto::proto::Transaction::from`], so we
+        // don't include it specifically in the
+        // [`rpc_proto::proto::Transaction::key`] field.
+        None
+    } else {
+        // Include the protobuf encoding of the public key in the message.
+        Some(key_enc)
+    }
+}
+
+/// Returns the bytes to sign for a transaction published by `author`.
+fn signature_bytes(
+    author: &PeerId,
+    seqno: u64,
+    data: &[u8],
+    headers: &BTreeMap<String, Vec<u8>>,
+    priority: u32,
+) -> Vec<u8> {
+    let transaction = proto::Transaction {
+        from: author.to_bytes(),
+        seqno,
+        data: data.to_vec(),
+        headers: headers
+            .iter()
+            .map(|(key, value)| proto::TransactionHeader {
+                key: key.clone(),
+                value: value.clone(),
+            })
+            .collect(),
+        priority,
+        // Signature, key, hops and ttl fields are not included in the signature
+        signature: vec![],
+        key: vec![],
+        hops: 0,
+        ttl: 0,
+    };
+
+    let mut buf = Vec::with_capacity(transaction.get_size());
+    let mut writer = Writer::new(&mut buf);
+
+    transaction
+        .write_message(&mut writer)
+        .expect("Encoding to succeed");
+
+    let mut signature_bytes = SIGNING_PREFIX.to_vec();
+    signature_bytes.extend_from_slice(&buf);
+    signature_bytes
+}
+
+/// The key signing the transactions published by the local node.
+enum Signer {
+    Keypair(Box<Keypair>),
+    Custom(Arc<dyn TransactionSigner>),
+}
+
+/// A transaction to publish whose author and sequence number have been allocated, signed once its
+/// data has been transformed.
+struct UnsignedTransaction {
+    from: Option<PeerId>,
+    seqno: Option<u64>,
+    headers: BTreeMap<String, Vec<u8>>,
+    priority: u32,
+    ttl: Option<NonZeroU32>,
+    key: Option<Vec<u8>>,
+    signer: Option<Signer>,
+}
+
+impl UnsignedTransaction {
+    /// Builds the [`RawTransaction`] with the given data. The returned future resolves once the
+    /// transaction has been signed, if needed.
+    fn sign(self, data: Vec<u8>) -> BoxFuture<'static, Result<RawTransaction, PublishError>> {
+        let UnsignedTransaction {
+            from,
+            seqno,
+            headers,
+            priority,
+            ttl,
+            key,
+            signer,
+        } = self;
+
+        let signature = match (signer, from, seqno) {
+            (Some(signer), Some(author), Some(seqno)) => {
+                let bytes = signature_bytes(&author, seqno, &data, &headers, priority);
+                match signer {
+                    Signer::Keypair(keypair) => {
+                        future::ready(keypair.sign(&bytes).map(Some).map_err(PublishError::from))
+                            .boxed()
+                    }
+                    Signer::Custom(signer) => signer
+                        .sign(bytes)
+                        .map(|signature| signature.map(Some).map_err(PublishError::SignerFailed))
+                        .boxed(),
+                }
+            }
+            _ => future::ready(Ok(None)).boxed(),
+        };
+
+        signature
+            .map(move |signature| {
+                signature.map(|signature| RawTransaction {
+                    from,
+                    seqno,
+                    data,
+                    headers,
+                    priority,
+                    signature,
+                    key,
+                    hops: 0,
+                    ttl,
+                })
+            })
+            .boxed()
+    }
+}
+
+/// Transforms the data of a transaction to publish, checking the size of the result.
+fn transform_outbound<D: DataTransform>(
+    data_transform: &D,
+    data: &[u8],
+    context: &OutboundContext,
+    max_transmit_size: usize,
+) -> Result<Vec<u8>, PublishError> {
+    let transformed_data =
+        data_transform.outbound_transform_with_context(data.to_vec(), context)?;
+
+    if transformed_data.len() > max_transmit_size {
+        return Err(PublishError::TransactionTooLarge);
+    }
+
+    Ok(transformed_data)
+}
+
+/// The maximum number of publish requests taken from the [`DogPublisher`] queue in a single poll.
+const MAX_PUBLISH_REQUESTS_PER_POLL: usize = 64;
+
 /// Network behaviour that handles the dog protocol.
 ///
 /// NOTE: Initialisation requires a [`TransactionAuthenticity`]  and [`Config`] instance.
@@ -153,12 +417,38 @@ pub struct Behaviour<D = IdentityTransform> {
     config: Config,
     events: VecDeque<ToSwarm<Event, HandlerIn>>,
     publish_config: PublishConfig,
-    data_transform: D,
+    data_transform: Arc<D>,
     connected_peers: HashMap<PeerId, PeerConnections>,
     redundancy_interval: Delay,
     redundancy_controller: Controller,
     router: Router,
-    cache: DuplicateCache<TransactionId, PeerId>,
+    known_transactions: KnownTransactions,
+    cache: Box<dyn DuplicateFilter>,
+    /// Map of the fast ids of the recently received transactions to their transaction ids.
+    fast_transaction_ids: DuplicateCache<TransactionId, TransactionId>,
+    seqno_windows: Option<SequenceNumberWindows>,
+    publish_sender: mpsc::Sender<PublishRequest>,
+    publish_receiver: mpsc::Receiver<PublishRequest>,
+    publish_outcomes: PublishOutcomes,
+    /// The transactions requested by [`DogPublisher`] handles, being transformed and signed. They
+    /// are published in the order of their sequence numbers.
+    pending_publishes: FuturesOrdered<
+        BoxFuture<
+            'static,
+            (
+                Result<RawTransaction, PublishError>,
+                Vec<u8>,
+                oneshot::Sender<Result<TransactionId, PublishError>>,
+            ),
+        >,
+    >,
+    published_transactions: PublishedTransactions,
+    publish_retries: FuturesUnordered<BoxFuture<'static, TransactionId>>,
+    publish_buffer: PublishBuffer,
+    store: Option<TransactionStore>,
+    sync: SyncState,
+    reconcile_interval: Option<Delay>,
+    rate_limiters: HashMap<PeerId, PeerRateLimiter>,
     metrics: Option<Metrics>,
 }
 
@@ -192,17 +482,72 @@ where
         metrics: Option<&mut Registry>,
         data_transform: D,
     ) -> Result<Self, &'static str> {
-        // TODO: validate config
+        // We do not allow configurations where a published transaction would fail validation on
+        // our own node.
+        validate_config(&authenticity, config.validation_mode())?;
+
+        let (publish_sender, publish_receiver) = mpsc::channel(config.publish_request_queue_len());
 
         Ok(Self {
             events: VecDeque::new(),
             publish_config: PublishConfig::from(authenticity),
-            data_transform,
+            data_transform: Arc::new(data_transform),
             connected_peers: HashMap::new(),
             redundancy_interval: Delay::new(config.redundancy_interval()),
             redundancy_controller: Controller::new(&config),
-            router: Router::new(),
-            cache: DuplicateCache::new(config.cache_time()),
+            router: Router::new(&config),
+            known_transactions: KnownTransactions::new(
+                config.cache_time(),
+                config.max_known_transactions_per_peer(),
+            ),
+            fast_transaction_ids: DuplicateCache::new(config.cache_time(), config.cache_capacity()),
+            cache: match *config.duplicate_filter_mode() {
+                DuplicateFilterMode::Exact => Box::new(DuplicateCache::new(
+                    config.cache_time(),
+                    config.cache_capacity(),
+                )),
+                DuplicateFilterMode::Bloom {
+                    capacity,
+                    false_positive_rate,
+                    source_window,
+                } => Box::new(RotatingBloomFilter::new(
+                    capacity,
+                    false_positive_rate,
+                    source_window,
+                    config.cache_time(),
+                )),
+            },
+            seqno_windows: config.replay_window().map(|size| {
+                SequenceNumberWindows::new(
+                    size,
+                    config.replay_author_ttl(),
+                    config.replay_max_authors(),
+                )
+            }),
+            publish_sender,
+            publish_receiver,
+            // Peers that have not reported after twice the publish queue duration are considered
+            // to have dropped the transaction.
+            publish_outcomes: PublishOutcomes::new(config.publish_queue_duration() * 2),
+            pending_publishes: FuturesOrdered::new(),
+            published_transactions: PublishedTransactions::new(
+                config.published_transactions_capacity(),
+            ),
+            publish_retries: FuturesUnordered::new(),
+            publish_buffer: PublishBuffer::new(
+                config.max_pending_publishes(),
+                config.pending_publish_ttl(),
+            ),
+            store: (config.max_stored_transactions() > 0).then(|| {
+                TransactionStore::new(
+                    config.max_stored_transactions(),
+                    config.max_stored_bytes(),
+                    config.cache_time(),
+                )
+            }),
+            sync: SyncState::new(config.sync_min_interval()),
+            reconcile_interval: config.reconcile_interval().map(Delay::new),
+            rate_limiters: HashMap::new(),
             config,
             metrics: metrics.map(Metrics::new),
         })
@@ -211,23 +556,112 @@ where
 
 impl<D> Behaviour<D>
 where
-    D: DataTransform + Send + 'static,
+    D: DataTransform + Send + Sync + 'static,
 {
+    /// Publishes a transaction to the network.
+    ///
+    /// See [`Behaviour::publisher`] to publish transactions from outside of the swarm task, which
+    /// is required with [`TransactionAuthenticity::Custom`]. A transaction published while
+    /// [`DogPublisher`] requests are being transformed or signed may be sent before them, with a
+    /// higher sequence number.
     pub fn publish(&mut self, data: impl Into<Vec<u8>>) -> Result<TransactionId, PublishError> {
+        self.publish_with_options(data, PublishOptions::default())
+    }
+
+    /// Publishes a transaction to the network with options passed to the outbound transform. See
+    /// [`Behaviour::publish`].
+    pub fn publish_with_options(
+        &mut self,
+        data: impl Into<Vec<u8>>,
+        options: PublishOptions,
+    ) -> Result<TransactionId, PublishError> {
+        if let PublishConfig::Custom { .. } = self.publish_config {
+            return Err(PublishError::AsyncSigner);
+        }
+
         let data = data.into();
+        let unsigned_transaction = self.prepare_transaction(&options);
+        let transformed_data = transform_outbound(
+            &*self.data_transform,
+            &data,
+            &self.outbound_context(&unsigned_transaction, &options),
+            self.config.max_transmit_size(),
+        )?;
+
+        // Only custom signers are asynchronous.
+        let raw_transaction = unsigned_transaction
+            .sign(transformed_data)
+            .now_or_never()
+            .expect("Transactions not signed by a custom signer to be built immediately")?;
+
+        self.publish_raw_transaction(raw_transaction, data)
+    }
+
+    /// Returns a recently seen transaction, either received or published by this node, if it is
+    /// still in the transaction store. See [`Config::max_stored_transactions`].
+    pub fn get_transaction(&self, tx_id: &TransactionId) -> Option<&Transaction> {
+        self.store.as_ref().and_then(|store| store.get(tx_id))
+    }
 
-        let transformed_data = self.data_transform.outbound_transform(data.clone())?;
+    /// Returns a cloneable [`DogPublisher`] handle to queue transactions to be published by this
+    /// behaviour.
+    pub fn publisher(&self) -> DogPublisher {
+        DogPublisher::new(self.publish_sender.clone())
+    }
 
-        if transformed_data.len() > self.config.max_transmit_size() {
-            return Err(PublishError::TransactionTooLarge);
+    fn handle_publish_request(
+        &mut self,
+        PublishRequest {
+            data,
+            options,
+            response,
+        }: PublishRequest,
+    ) {
+        // The sequence number is allocated now, so that transactions are published in the order
+        // of the requests.
+        let unsigned_transaction = self.prepare_transaction(&options);
+        let context = self.outbound_context(&unsigned_transaction, &options);
+        let data_transform = self.data_transform.clone();
+        let max_transmit_size = self.config.max_transmit_size();
+
+        let pending_publish = async move {
+            let raw_transaction =
+                match transform_outbound(&*data_transform, &data, &context, max_transmit_size) {
+                    Ok(transformed_data) => unsigned_transaction.sign(transformed_data).await,
+                    Err(e) => Err(e),
+                };
+            (raw_transaction, data, response)
         }
+        .boxed();
+        self.pending_publishes.push_back(pending_publish);
+    }
 
-        let raw_transaction = self.build_raw_transaction(transformed_data)?;
+    fn outbound_context(
+        &self,
+        unsigned_transaction: &UnsignedTransaction,
+        options: &PublishOptions,
+    ) -> OutboundContext {
+        OutboundContext {
+            author: unsigned_transaction.from,
+            seqno: unsigned_transaction.seqno,
+            peers: self.router.filter_valid_routes(
+                self.publish_config.get_own_id(),
+                self.connected_peers.keys().cloned().collect::<Vec<_>>(),
+            ),
+            options: options.clone(),
+        }
+    }
 
+    fn publish_raw_transaction(
+        &mut self,
+        raw_transaction: RawTransaction,
+        data: Vec<u8>,
+    ) -> Result<TransactionId, PublishError> {
         let transaction = Transaction {
             from: raw_transaction.from,
             seqno: raw_transaction.seqno,
             data,
+            headers: raw_transaction.headers.clone(),
         };
 
         let tx_id = self.config.transaction_id(&transaction);
@@ -237,13 +671,35 @@ where
             return Err(PublishError::Duplicate);
         }
 
+        // The transaction is only recorded once it can be sent or buffered, so that publishing
+        // it again after a failure is not rejected as a duplicate.
+        let buffered = self.publish_buffer.is_enabled() && !self.is_protocol_peer_connected();
+        if !buffered {
+            let recipient_peers = self.router.filter_valid_routes(
+                self.publish_config.get_own_id(),
+                self.connected_peers.keys().cloned().collect::<Vec<_>>(),
+            );
+            if recipient_peers.is_empty() {
+                return Err(PublishError::InsufficientPeers);
+            }
+            if recipient_peers.iter().all(|peer_id| {
+                self.connected_peers
+                    .get(peer_id)
+                    .is_none_or(|peer| peer.sender.is_publish_queue_full())
+            }) {
+                return Err(PublishError::AllQueuesFull(self.connected_peers.len()));
+            }
+        }
+
         tracing::trace!("Publishing transaction");
 
         self.cache
             .insert(tx_id.clone(), self.publish_config.get_own_id());
 
-        if let Some(m) = self.metrics.as_mut() {
-            m.set_txs_cache_size(self.cache.len());
+        self.update_cache_metrics();
+
+        if let Some(store) = self.store.as_mut() {
+            store.insert(tx_id.clone(), raw_transaction.clone(), transaction.clone());
         }
 
         if self.config.deliver_own_transactions() {
@@ -252,25 +708,60 @@ where
                     propagation_source: self.publish_config.get_own_id(),
                     transaction_id: tx_id.clone(),
                     transaction,
+                    hops: 0,
                 }));
         }
 
+        self.published_transactions
+            .insert(tx_id.clone(), raw_transaction.clone());
+
+        if buffered {
+            tracing::debug!(transaction=%tx_id, "No DOG peer connected, buffering transaction");
+            if let Some(evicted) = self.publish_buffer.push(tx_id.clone(), raw_transaction) {
+                self.emit_publish_expired(evicted);
+            }
+        } else if let Err(e) = self.send_published_transaction(&tx_id, raw_transaction) {
+            // Routes and queue capacity have been checked before recording the transaction.
+            tracing::error!(transaction=%tx_id, "Failed to send published transaction: {e}");
+        }
+
+        tracing::debug!(transaction=%tx_id, "Published transaction");
+
+        if let Some(m) = self.metrics.as_mut() {
+            m.register_published_tx();
+        }
+
+        Ok(tx_id)
+    }
+
+    /// Sends one of our own transactions to the peers we have a valid route to.
+    fn send_published_transaction(
+        &mut self,
+        tx_id: &TransactionId,
+        raw_transaction: RawTransaction,
+    ) -> Result<(), PublishError> {
         let recipient_peers = self.router.filter_valid_routes(
             self.publish_config.get_own_id(),
             self.connected_peers.keys().cloned().collect::<Vec<_>>(),
         );
 
-        let mut publish_failed = true;
+        let mut queued_to = Vec::with_capacity(recipient_peers.len());
+        let mut dropped_at = Vec::new();
         for peer_id in &recipient_peers {
             tracing::trace!(peer=%peer_id, "Sending transaction to peer");
             if self.send_transaction(
                 *peer_id,
                 RpcOut::Publish {
                     tx: raw_transaction.clone(),
+                    tx_id: tx_id.clone(),
                     timeout: Delay::new(self.config.publish_queue_duration()),
                 },
             ) {
-                publish_failed = false;
+                self.known_transactions
+                    .register_sent(*peer_id, tx_id.clone());
+                queued_to.push(*peer_id);
+            } else {
+                dropped_at.push(*peer_id);
             }
         }
 
@@ -278,70 +769,115 @@ where
             return Err(PublishError::InsufficientPeers);
         }
 
-        if publish_failed {
+        if queued_to.is_empty() {
             return Err(PublishError::AllQueuesFull(self.connected_peers.len()));
         }
 
-        tracing::debug!(transaction=%tx_id, "Published transaction");
+        self.publish_outcomes
+            .track(tx_id.clone(), queued_to, dropped_at);
 
-        if let Some(m) = self.metrics.as_mut() {
-            m.register_published_tx();
+        Ok(())
+    }
+
+    /// Sends again a transaction previously published by this node, with the same sequence number
+    /// and signature. This is useful when the previous sends timed out or the transaction reached
+    /// no peer.
+    ///
+    /// Only the last [`Config::published_transactions_capacity`] published transactions can be
+    /// republished.
+    pub fn republish(&mut self, tx_id: &TransactionId) -> Result<(), PublishError> {
+        let Some(published) = self.published_transactions.get(tx_id) else {
+            return Err(PublishError::UnknownTransaction);
+        };
+        let raw_transaction = published.raw_transaction.clone();
+
+        // The transaction id may have expired from the cache since it was first published.
+        self.cache
+            .insert(tx_id.clone(), self.publish_config.get_own_id());
+
+        self.update_cache_metrics();
+
+        self.send_published_transaction(tx_id, raw_transaction)?;
+
+        tracing::debug!(transaction=%tx_id, "Republished transaction");
+
+        Ok(())
+    }
+
+    /// Schedules an automatic republish of a transaction that could not be sent to any peer, if
+    /// retries remain.
+    fn schedule_publish_retry(&mut self, tx_id: &TransactionId) {
+        let Some(published) = self.published_transactions.get_mut(tx_id) else {
+            return;
+        };
+        if published.retries >= self.config.publish_max_retries() {
+            return;
         }
+        published.retries += 1;
 
-        Ok(tx_id)
+        tracing::debug!(
+            transaction=%tx_id,
+            "Scheduling republish attempt {}",
+            published.retries
+        );
+
+        let tx_id = tx_id.clone();
+        self.publish_retries.push(
+            Delay::new(self.config.publish_retry_delay())
+                .map(move |_| tx_id)
+                .boxed(),
+        );
     }
 
-    fn build_raw_transaction(&mut self, data: Vec<u8>) -> Result<RawTransaction, PublishError> {
-        match &mut self.publish_config {
+    fn retry_publish(&mut self, tx_id: TransactionId) {
+        if let Err(e) = self.republish(&tx_id) {
+            tracing::debug!(transaction=%tx_id, "Failed to republish transaction: {e}");
+            self.schedule_publish_retry(&tx_id);
+        }
+    }
+
+    /// Allocates the author and sequence number of a transaction to publish.
+    fn prepare_transaction(&mut self, options: &PublishOptions) -> UnsignedTransaction {
+        let (from, seqno, key, signer) = match &mut self.publish_config {
             PublishConfig::Signing {
-                ref keypair,
+                keypair,
                 author,
                 inline_key,
                 last_seqno,
-            } => {
-                let seqno = last_seqno.next();
-
-                let signature = {
-                    let transaction = proto::Transaction {
-                        from: author.to_bytes(),
-                        seqno,
-                        data: data.clone(),
-                        // Signature and key fields are not included in the signature
-                        signature: vec![],
-                        key: vec![],
-                    };
-
-                    let mut buf = Vec::with_capacity(transaction.get_size());
-                    let mut writer = Writer::new(&mut buf);
-
-                    transaction
-                        .write_message(&mut writer)
-                        .expect("Encoding to succeed");
-
-                    let mut signature_bytes = SIGNING_PREFIX.to_vec();
-                    signature_bytes.extend_from_slice(&buf);
-                    keypair.sign(&signature_bytes)?
-                };
-
-                Ok(RawTransaction {
-                    from: *author,
-                    seqno,
-                    data,
-                    signature: Some(signature.to_vec()),
-                    key: inline_key.clone(),
-                })
-            }
+            } => (
+                Some(*author),
+                Some(last_seqno.next()),
+                inline_key.clone(),
+                Some(Signer::Keypair(Box::new(keypair.clone()))),
+            ),
+            PublishConfig::Custom {
+                signer,
+                author,
+                inline_key,
+                last_seqno,
+            } => (
+                Some(*author),
+                Some(last_seqno.next()),
+                inline_key.clone(),
+                Some(Signer::Custom(signer.clone())),
+            ),
             PublishConfig::Author { author, last_seqno } => {
-                let seqno = last_seqno.next();
-
-                Ok(RawTransaction {
-                    from: *author,
-                    seqno,
-                    data,
-                    signature: None,
-                    key: None,
-                })
+                (Some(*author), Some(last_seqno.next()), None, None)
             }
+            PublishConfig::RandomAuthor { .. } => {
+                (Some(PeerId::random()), Some(rand::random()), None, None)
+            }
+            PublishConfig::Anonymous { .. } => (None, None, None, None),
+        };
+
+        UnsignedTransaction {
+            from,
+            seqno,
+            headers: options.headers.clone(),
+            priority: options.priority,
+            ttl: options.ttl,
+            key,
+            signer,
         }
     }
 
@@ -372,16 +908,36 @@ where
     fn forward_transaction(
         &mut self,
         transaction_id: &TransactionId,
-        raw_transaction: RawTransaction,
+        mut raw_transaction: RawTransaction,
         propagation_source: &PeerId,
     ) -> bool {
+        if let Some(ttl) = raw_transaction.ttl {
+            match NonZeroU32::new(ttl.get() - 1) {
+                Some(ttl) => raw_transaction.ttl = Some(ttl),
+                None => {
+                    tracing::debug!(transaction=%transaction_id, "Transaction time-to-live expired, not forwarding");
+                    if let Some(m) = self.metrics.as_mut() {
+                        m.register_ttl_expired();
+                    }
+                    return false;
+                }
+            }
+        }
+        raw_transaction.hops = raw_transaction.hops.saturating_add(1);
+
         tracing::debug!(transaction=%transaction_id, "Forwarding transaction");
 
         let recipient_peers = self.router.filter_valid_routes(
             *propagation_source,
             self.connected_peers
                 .keys()
-                .filter(|&peer| peer != propagation_source && peer != &raw_transaction.from)
+                .filter(|&peer| {
+                    peer != propagation_source
+                        && Some(*peer) != raw_transaction.from
+                        && !self
+                            .known_transactions
+                            .has_transaction(peer, transaction_id)
+                })
                 .cloned()
                 .collect::<Vec<_>>(),
         );
@@ -392,13 +948,16 @@ where
 
         for peer_id in &recipient_peers {
             tracing::trace!(peer=%peer_id, "Forwarding transaction to peer");
-            self.send_transaction(
+            if self.send_transaction(
                 *peer_id,
                 RpcOut::Forward {
                     tx: raw_transaction.clone(),
                     timeout: Delay::new(self.config.forward_queue_duration()),
                 },
-            );
+            ) {
+                self.known_transactions
+                    .register_sent(*peer_id, transaction_id.clone());
+            }
         }
 
         tracing::debug!("Completed forwarding transaction");
@@ -407,13 +966,47 @@ where
 
     fn on_connection_established(
         &mut self,
-        ConnectionEstablished { peer_id, .. }: ConnectionEstablished,
+        ConnectionEstablished {
+            peer_id,
+            other_established,
+            ..
+        }: ConnectionEstablished,
     ) {
         tracing::debug!(peer=%peer_id, "New peer connected");
 
         if let Some(m) = self.metrics.as_mut() {
             m.inc_peers_count();
         }
+
+        if other_established == 0 && self.config.sync_on_connection() {
+            tracing::debug!(peer=%peer_id, "Requesting sync summary from peer");
+            if self.send_transaction(peer_id, RpcOut::SyncRequest(SyncRequest { tx_ids: vec![] })) {
+                self.sync.request(peer_id);
+            }
+        }
+    }
+
+    /// Returns `true` if a connected peer is known to support the DOG protocol.
+    fn is_protocol_peer_connected(&self) -> bool {
+        self.connected_peers
+            .values()
+            .any(|peer| peer.protocol_supported)
+    }
+
+    /// Sends the transactions published while no DOG peer was connected.
+    fn flush_publish_buffer(&mut self) {
+        for tx_id in self.publish_buffer.remove_expired() {
+            self.emit_publish_expired(tx_id);
+        }
+
+        let buffered = self.publish_buffer.drain().collect::<Vec<_>>();
+        for (tx_id, raw_transaction) in buffered {
+            tracing::debug!(transaction=%tx_id, "Sending buffered transaction");
+            if let Err(e) = self.send_published_transaction(&tx_id, raw_transaction) {
+                tracing::debug!(transaction=%tx_id, "Failed to send buffered transaction: {e}");
+                self.schedule_publish_retry(&tx_id);
+            }
+        }
     }
 
     fn on_connection_closed(
@@ -432,6 +1025,8 @@ where
         } else {
             tracing::debug!(peer=%peer_id, "Peer disconnected");
 
+            self.known_transactions.remove_peer(&peer_id);
+
             if !self.router.reset_routes_with_peer(peer_id).is_empty() {
                 self.events
                     .push_back(ToSwarm::GenerateEvent(Event::RoutingUpdated {
@@ -445,12 +1040,119 @@ where
             self.connected_peers.remove(&peer_id);
             self.adjust_redundancy();
 
+            for outcome in self.publish_outcomes.peer_disconnected(peer_id) {
+                self.emit_publish_outcome(outcome);
+            }
+
+            self.sync.peer_disconnected(&peer_id);
+            self.rate_limiters.remove(&peer_id);
+
             if let Some(m) = self.metrics.as_mut() {
                 m.dec_peers_count();
             }
         }
     }
 
+    fn rate_limiter(&mut self, peer_id: PeerId) -> &mut PeerRateLimiter {
+        self.rate_limiters
+            .entry(peer_id)
+            .or_insert_with(|| PeerRateLimiter::new(&self.config))
+    }
+
+    fn handle_rate_limited(
+        &mut self,
+        peer_id: PeerId,
+        dropped_transactions: usize,
+        dropped_control_messages: usize,
+    ) {
+        tracing::warn!(
+            peer=%peer_id,
+            "Peer exceeded its rate limits. Dropped {} transactions and {} control messages",
+            dropped_transactions,
+            dropped_control_messages,
+        );
+
+        if let Some(m) = self.metrics.as_mut() {
+            m.register_rate_limited(dropped_transactions, dropped_control_messages);
+        }
+
+        self.events
+            .push_back(ToSwarm::GenerateEvent(Event::PeerRateLimited {
+                peer_id,
+                dropped_transactions,
+                dropped_control_messages,
+            }));
+    }
+
+    fn update_cache_metrics(&mut self) {
+        let (expired, evicted) = self.cache.take_removed();
+        if let Some(m) = self.metrics.as_mut() {
+            m.register_txs_cache_removed(expired, evicted);
+            m.set_txs_cache_size(self.cache.len(), self.cache.memory());
+        }
+    }
+
+    fn emit_publish_expired(&mut self, transaction_id: TransactionId) {
+        tracing::debug!(transaction=%transaction_id, "Buffered transaction dropped");
+
+        self.events
+            .push_back(ToSwarm::GenerateEvent(Event::PublishExpired {
+                transaction_id,
+            }));
+    }
+
+    fn emit_publish_outcome(&mut self, outcome: PublishOutcome) {
+        tracing::debug!(
+            transaction=%outcome.transaction_id,
+            "Published transaction sent to {} peers, dropped at {} peers",
+            outcome.sent_to.len(),
+            outcome.dropped_at.len(),
+        );
+
+        if outcome.sent_to.is_empty() {
+            self.schedule_publish_retry(&outcome.transaction_id);
+        }
+
+        self.events
+            .push_back(ToSwarm::GenerateEvent(Event::PublishOutcome {
+                transaction_id: outcome.transaction_id,
+                sent_to: outcome.sent_to,
+                dropped_at: outcome.dropped_at,
+            }));
+    }
+
+    fn handle_duplicate_transaction(
+        &mut self,
+        tx_id: TransactionId,
+        propagation_source: &PeerId,
+        synced: bool,
+    ) {
+        tracing::debug!(transaction=%tx_id, "Transaction already received, ignoring");
+
+        self.update_cache_metrics();
+
+        if synced {
+            return;
+        }
+
+        self.redundancy_controller.incr_duplicate_txs_count();
+
+        if self.redundancy_controller.is_have_tx_blocked() {
+            return;
+        }
+
+        tracing::debug!(peer=%propagation_source, "Sending HaveTx to peer");
+
+        if self.send_transaction(*propagation_source, RpcOut::HaveTx(HaveTx { tx_id })) {
+            self.router.register_have_tx_sent(*propagation_source);
+            self.redundancy_controller.block_have_tx();
+
+            if let Some(m) = self.metrics.as_mut() {
+                m.register_have_tx_sent();
+            }
+        }
+    }
+
     fn handle_received_transaction(
         &mut self,
         raw_transaction: RawTransaction,
@@ -460,53 +1162,95 @@ where
             m.tx_recv_unfiltered(raw_transaction.raw_protobuf_len());
         }
 
-        let transaction = match self
-            .data_transform
-            .inbound_transform(raw_transaction.clone())
+        let fast_tx_id = self.config.fast_transaction_id(&raw_transaction);
+        if let Some(tx_id) = fast_tx_id
+            .as_ref()
+            .and_then(|fast_tx_id| self.fast_transaction_ids.get(fast_tx_id))
+            .cloned()
         {
+            self.known_transactions
+                .register_received(*propagation_source, tx_id.clone());
+            let synced = self.sync.transaction_received(propagation_source, &tx_id);
+            self.handle_duplicate_transaction(tx_id, propagation_source, synced);
+            return;
+        }
+
+        let transaction = match self.data_transform.inbound_transform_with_context(
+            raw_transaction.clone(),
+            &InboundContext {
+                propagation_source: *propagation_source,
+                author: raw_transaction.from,
+                seqno: raw_transaction.seqno,
+                headers: raw_transaction.headers.clone(),
+            },
+        ) {
             Ok(transaction) => transaction,
             Err(e) => {
                 tracing::debug!("Invalid transaction. Transform error: {:?}", e);
-                self.handle_invalid_transaction(propagation_source, raw_transaction);
+                self.handle_invalid_transaction(
+                    propagation_source,
+                    raw_transaction,
+                    ValidationError::TransformFailed,
+                );
                 return;
             }
         };
 
         let tx_id = self.config.transaction_id(&transaction);
 
-        // TODO: validate transaction if needed
-
-        if !self.cache.insert(tx_id.clone(), *propagation_source) {
-            tracing::debug!(transaction=%tx_id, "Transaction already received, ignoring");
+        if self.cache.contains(&tx_id) {
+            self.known_transactions
+                .register_received(*propagation_source, tx_id.clone());
+            let synced = self.sync.transaction_received(propagation_source, &tx_id);
+            self.handle_duplicate_transaction(tx_id, propagation_source, synced);
+            return;
+        }
 
-            if let Some(m) = self.metrics.as_mut() {
-                m.set_txs_cache_size(self.cache.len());
+        // Replayed transactions are rejected before they are recorded anywhere, so that they are
+        // neither stored nor treated as duplicates when received again.
+        if let (Some(windows), Some(from), Some(seqno)) = (
+            self.seqno_windows.as_mut(),
+            raw_transaction.from,
+            raw_transaction.seqno,
+        ) {
+            if !windows.insert(from, seqno) {
+                tracing::debug!(transaction=%tx_id, "Replayed transaction, ignoring");
+                self.handle_invalid_transaction(
+                    propagation_source,
+                    raw_transaction,
+                    ValidationError::Replay,
+                );
+                return;
             }
+        }
 
-            self.redundancy_controller.incr_duplicate_txs_count();
+        if let Some(fast_tx_id) = fast_tx_id {
+            self.fast_transaction_ids.insert(fast_tx_id, tx_id.clone());
+        }
 
-            if self.redundancy_controller.is_have_tx_blocked() {
-                return;
-            }
+        self.known_transactions
+            .register_received(*propagation_source, tx_id.clone());
 
-            tracing::debug!(peer=%propagation_source, "Sending HaveTx to peer");
+        // Transactions fetched during a sync are not forwarded and do not count towards the
+        // redundancy, as they were not routed to us.
+        let synced = self.sync.transaction_received(propagation_source, &tx_id);
 
-            if self.send_transaction(*propagation_source, RpcOut::HaveTx(HaveTx { tx_id })) {
-                self.router.register_have_tx_sent(*propagation_source);
-                self.redundancy_controller.block_have_tx();
+        // TODO: validate transaction if needed
 
-                if let Some(m) = self.metrics.as_mut() {
-                    m.register_have_tx_sent();
-                }
-            }
+        self.cache.insert(tx_id.clone(), *propagation_source);
 
-            return;
+        if !synced {
+            self.redundancy_controller.incr_first_time_txs_count();
         }
-        self.redundancy_controller.incr_first_time_txs_count();
 
         if let Some(m) = self.metrics.as_mut() {
             m.tx_recv();
-            m.set_txs_cache_size(self.cache.len());
+        }
+
+        self.update_cache_metrics();
+
+        if let Some(store) = self.store.as_mut() {
+            store.insert(tx_id.clone(), raw_transaction.clone(), transaction.clone());
         }
 
         tracing::debug!("Deliver received transaction to user");
@@ -515,35 +1259,112 @@ where
                 propagation_source: *propagation_source,
                 transaction_id: tx_id.clone(),
                 transaction,
+                hops: raw_transaction.hops,
             }));
 
-        if self.config.forward_transactions() {
+        if self.config.forward_transactions() && !synced {
             self.forward_transaction(&tx_id, raw_transaction, propagation_source);
         }
     }
 
     fn handle_invalid_transaction(
         &mut self,
-        _propagation_source: &PeerId,
-        _raw_transaction: RawTransaction,
-        // rejection_reason: ???
+        propagation_source: &PeerId,
+        raw_transaction: RawTransaction,
+        rejection_reason: ValidationError,
     ) {
+        tracing::debug!(
+            peer=%propagation_source,
+            from=?raw_transaction.from,
+            seqno=?raw_transaction.seqno,
+            "Invalid transaction. Reason: {}",
+            rejection_reason,
+        );
+
         if let Some(m) = self.metrics.as_mut() {
             m.register_invalid_tx();
+
+            if let ValidationError::Replay = rejection_reason {
+                m.register_replay_tx();
+            }
         }
     }
 
     fn handle_have_tx(&mut self, tx_ids: Vec<TransactionId>, propagation_source: &PeerId) {
         tracing::debug!(peer=%propagation_source, "Received HaveTx from peer with {} transaction ids", tx_ids.len());
 
+        let mut unsolicited = 0;
         for tx_id in tx_ids {
-            if let Some(source) = self.cache.get(&tx_id) {
-                if *source == *propagation_source {
+            match self.known_transactions.was_sent(propagation_source, &tx_id) {
+                Some(true) => {}
+                Some(false) => {
+                    unsolicited += 1;
+                    continue;
+                }
+                None => {
+                    // The transaction may have been sent and evicted since, so it is ignored
+                    // without being reported.
+                    tracing::debug!(peer=%propagation_source, transaction=%tx_id, "Ignoring HaveTx from peer for an unknown transaction");
+                    continue;
+                }
+            }
+            if let Some(source) = self.cache.source(&tx_id) {
+                if source == *propagation_source {
                     continue;
                 }
                 tracing::debug!(peer=%propagation_source, "Disabling route from {} to peer", source);
-                self.router.disable_route(*source, *propagation_source);
+                match self.router.disable_route(
+                    source,
+                    *propagation_source,
+                    self.connected_peers.len(),
+                ) {
+                    Ok(()) => {}
+                    Err(DisableRouteRejection::MaxDisabledRoutes) => {
+                        // Honest peers keep sending `HaveTx` while they receive duplicates, so this
+                        // is not reported as a misbehaviour.
+                        tracing::debug!(
+                            peer=%propagation_source,
+                            "Not disabling route from {} to peer, maximum number of disabled routes reached",
+                            source
+                        );
+
+                        if let Some(m) = self.metrics.as_mut() {
+                            m.register_disabled_routes_cap_reached();
+                        }
+                    }
+                    Err(DisableRouteRejection::MinActiveRoutes) => {
+                        tracing::debug!(
+                            peer=%propagation_source,
+                            "Not disabling route from {} to peer, minimum number of active routes reached",
+                            source
+                        );
+
+                        if let Some(m) = self.metrics.as_mut() {
+                            m.register_disable_route_rejected();
+                        }
+                    }
+                }
+            }
+        }
+
+        if unsolicited > 0 {
+            tracing::warn!(
+                peer=%propagation_source,
+                "Ignoring HaveTx from peer for {} transactions never sent to it",
+                unsolicited
+            );
+
+            if let Some(m) = self.metrics.as_mut() {
+                m.register_have_tx_rejected(unsolicited);
             }
+
+            self.events
+                .push_back(ToSwarm::GenerateEvent(Event::PeerMisbehaviour {
+                    peer_id: *propagation_source,
+                    misbehaviour: Misbehaviour::UnsolicitedHaveTx {
+                        transactions: unsolicited,
+                    },
+                }));
         }
 
         self.events
@@ -559,8 +1380,8 @@ where
     fn handle_reset_route(&mut self, propagation_source: &PeerId) {
         tracing::debug!(peer=%propagation_source, "Re-enabling a random route to peer");
 
-        match self.router.enable_random_route_to_peer(*propagation_source) {
-            Some(route) => {
+        match self.router.reset_route(*propagation_source) {
+            Ok(route) => {
                 tracing::debug!(peer=%propagation_source, "Re-enabled route {} to peer", route);
 
                 self.events
@@ -572,12 +1393,192 @@ where
                     m.set_disabled_routes_count(self.router.get_disabled_routes().len());
                 }
             }
-            None => {
-                tracing::warn!(peer=%propagation_source, "No route to re-enable to peer");
+            Err(rejection) => {
+                tracing::warn!(
+                    peer=%propagation_source,
+                    ?rejection,
+                    "Ignoring reset route from peer"
+                );
+
+                if let Some(m) = self.metrics.as_mut() {
+                    m.register_reset_route_rejected();
+                }
+
+                if rejection == ResetRouteRejection::NeverDisabled {
+                    self.events
+                        .push_back(ToSwarm::GenerateEvent(Event::PeerMisbehaviour {
+                            peer_id: *propagation_source,
+                            misbehaviour: Misbehaviour::UnsolicitedResetRoute,
+                        }));
+                }
+            }
+        }
+    }
+
+    fn handle_sync_request(&mut self, tx_ids: Vec<TransactionId>, propagation_source: &PeerId) {
+        if tx_ids.is_empty() {
+            if !self.sync.serve_summary(*propagation_source) {
+                tracing::debug!(peer=%propagation_source, "Ignoring sync request received too early");
+                return;
+            }
+
+            let tx_ids = self
+                .store
+                .as_ref()
+                .map(|store| store.recent_ids(self.config.max_sync_transactions()))
+                .unwrap_or_default();
+
+            tracing::debug!(peer=%propagation_source, "Sending sync summary of {} transactions to peer", tx_ids.len());
+
+            self.send_transaction(
+                *propagation_source,
+                RpcOut::SyncSummary(SyncSummary { tx_ids }),
+            );
+            return;
+        }
+
+        if !self.sync.serve_fetch(propagation_source) {
+            tracing::debug!(peer=%propagation_source, "Ignoring sync fetch without a prior summary");
+            return;
+        }
+
+        let Some(store) = self.store.as_ref() else {
+            return;
+        };
+        let raw_transactions = tx_ids
+            .iter()
+            .take(self.config.max_sync_transactions())
+            .filter_map(|tx_id| store.get_raw(tx_id).cloned())
+            .collect::<Vec<_>>();
+
+        tracing::debug!(peer=%propagation_source, "Sending {} synced transactions to peer", raw_transactions.len());
+
+        for raw_transaction in raw_transactions {
+            self.send_transaction(
+                *propagation_source,
+                RpcOut::Forward {
+                    tx: raw_transaction,
+                    timeout: Delay::new(self.config.forward_queue_duration()),
+                },
+            );
+        }
+    }
+
+    fn handle_sync_summary(&mut self, tx_ids: Vec<TransactionId>, propagation_source: &PeerId) {
+        if !self.sync.summary_received(propagation_source) {
+            tracing::debug!(peer=%propagation_source, "Ignoring unrequested sync summary");
+            return;
+        }
+
+        let missing = tx_ids
+            .into_iter()
+            .filter(|tx_id| !self.cache.contains(tx_id))
+            .take(self.config.max_sync_transactions())
+            .collect::<Vec<_>>();
+
+        if missing.is_empty() {
+            return;
+        }
+
+        tracing::debug!(peer=%propagation_source, "Fetching {} missing transactions from peer", missing.len());
+
+        if self.send_transaction(
+            *propagation_source,
+            RpcOut::SyncRequest(SyncRequest {
+                tx_ids: missing.clone(),
+            }),
+        ) {
+            self.sync.fetch(*propagation_source, missing);
+        }
+    }
+
+    /// Builds a sketch of the transactions in the store, if it is enabled.
+    fn build_sketch(&self, size: usize) -> Option<Iblt> {
+        let store = self.store.as_ref()?;
+        let mut sketch = Iblt::new(size);
+        for tx_id in store.recent_ids(usize::MAX) {
+            sketch.insert(iblt::short_id(&tx_id));
+        }
+        Some(sketch)
+    }
+
+    /// Sends a sketch of our recent transactions to every peer.
+    fn reconcile(&mut self) {
+        let Some(sketch) = self.build_sketch(self.config.reconcile_sketch_size()) else {
+            return;
+        };
+        let sketch = sketch.to_bytes();
+
+        tracing::debug!("Sending reconciliation sketch to peers");
+
+        let peers = self.connected_peers.keys().cloned().collect::<Vec<_>>();
+        for peer_id in peers {
+            if self.send_transaction(
+                peer_id,
+                RpcOut::Reconcile(Reconcile {
+                    sketch: sketch.clone(),
+                }),
+            ) {
+                self.sync.request(peer_id);
             }
         }
     }
 
+    fn handle_reconcile(&mut self, sketch: Vec<u8>, propagation_source: &PeerId) {
+        if self.config.reconcile_interval().is_none() {
+            tracing::debug!(peer=%propagation_source, "Ignoring reconciliation, reconciliation is disabled");
+            return;
+        }
+
+        // Our sketch is built with the size of the remote one, which is bounded by ours.
+        if sketch.len() > iblt::encoded_len(self.config.reconcile_sketch_size()) {
+            tracing::debug!(peer=%propagation_source, "Ignoring reconciliation sketch larger than ours");
+            return;
+        }
+
+        if !self.sync.serve_summary(*propagation_source) {
+            tracing::debug!(peer=%propagation_source, "Ignoring reconciliation received too early");
+            return;
+        }
+
+        let Some(remote) = Iblt::from_bytes(&sketch) else {
+            tracing::debug!(peer=%propagation_source, "Invalid reconciliation sketch");
+            return;
+        };
+        let Some(mut local) = self.build_sketch(remote.len()) else {
+            return;
+        };
+        local.subtract(&remote);
+
+        let tx_ids = match local.decode() {
+            Some((local_only, _)) => {
+                let local_only = local_only.into_iter().collect::<HashSet<_>>();
+                self.store
+                    .as_ref()
+                    .map(|store| {
+                        store
+                            .recent_ids(usize::MAX)
+                            .into_iter()
+                            .filter(|tx_id| local_only.contains(&iblt::short_id(tx_id)))
+                            .take(self.config.max_sync_transactions())
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            }
+            None => {
+                tracing::debug!(peer=%propagation_source, "Reconciliation difference too large to decode");
+                Vec::new()
+            }
+        };
+
+        tracing::debug!(peer=%propagation_source, "Sending {} transactions missing from the sketch of peer", tx_ids.len());
+
+        self.send_transaction(
+            *propagation_source,
+            RpcOut::SyncSummary(SyncSummary { tx_ids }),
+        );
+    }
+
     fn adjust_redundancy(&mut self) {
         tracing::debug!("Adjusting redundancy");
 
@@ -608,12 +1609,39 @@ where
         }
 
         self.redundancy_controller.reset_counters();
+        self.router.reset_interval();
     }
 }
 
+/// Validates the combination of signing, privacy and transaction validation to ensure the
+/// configuration will not reject published transactions.
+fn validate_config(
+    authenticity: &TransactionAuthenticity,
+    validation_mode: &ValidationMode,
+) -> Result<(), &'static str> {
+    match validation_mode {
+        ValidationMode::Anonymous if authenticity.is_signing() => {
+            return Err("Cannot enable transaction signing with an Anonymous validation mode. Consider changing either the ValidationMode or TransactionAuthenticity");
+        }
+        ValidationMode::Anonymous if !authenticity.is_anonymous() => {
+            return Err("Published transactions contain an author but incoming transactions with an author will be rejected. Consider adjusting the validation or privacy settings in the config");
+        }
+        ValidationMode::Strict if !authenticity.is_signing() => {
+            tracing::warn!(
+                "Transactions will be published unsigned and incoming unsigned transactions will be rejected. Consider adjusting the validation or privacy settings in the config"
+            );
+        }
+        ValidationMode::Author if authenticity.is_anonymous() => {
+            return Err("Published transactions contain no author but incoming transactions without an author will be rejected. Consider adjusting the validation or privacy settings in the config");
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
 impl<D> NetworkBehaviour for Behaviour<D>
 where
-    D: DataTransform + Send + 'static,
+    D: DataTransform + Send + Sync + 'static,
 {
     type ConnectionHandler = Handler;
     type ToSwarm = Event;
@@ -630,7 +1658,12 @@ where
             .entry(peer_id)
             .or_insert(PeerConnections {
                 connections: Vec::new(),
-                sender: Sender::new(self.config.connection_handler_queue_len()),
+                sender: Sender::new(
+                    self.config.connection_handler_queue_len(),
+                    self.config.priority_class_weights(),
+                    self.config.max_forwarded_priority(),
+                ),
+                protocol_supported: false,
             });
 
         connected_peer.connections.push(connection_id);
@@ -651,7 +1684,12 @@ where
     ) -> Result<libp2p::swarm::THandler<Self>, libp2p::swarm::ConnectionDenied> {
         let connected_peer = self.connected_peers.entry(peer).or_insert(PeerConnections {
             connections: Vec::new(),
-            sender: Sender::new(self.config.connection_handler_queue_len()),
+            sender: Sender::new(
+                self.config.connection_handler_queue_len(),
+                self.config.priority_class_weights(),
+                self.config.max_forwarded_priority(),
+            ),
+            protocol_supported: false,
         });
 
         connected_peer.connections.push(_connection_id);
@@ -677,13 +1715,14 @@ where
                 for (transaction, validation_error) in invalid_transactions {
                     tracing::warn!(
                         peer=%propagation_source,
-                        from=%transaction.from,
+                        from=?transaction.from,
                         "Invalid transaction from peer. Reason: {}",
                         validation_error,
                     );
                 }
 
                 // Handle transactions
+                let mut dropped_transactions = 0;
                 for (count, raw_transaction) in rpc.transactions.into_iter().enumerate() {
                     if self.config.max_transactions_per_rpc().is_some()
                         && Some(count) >= self.config.max_transactions_per_rpc()
@@ -691,13 +1730,28 @@ where
                         tracing::warn!("Received more transactions than permitted. Ignoring further transactions. Processed: {}", count);
                         break;
                     }
+                    if !self
+                        .rate_limiter(propagation_source)
+                        .allow_transaction(raw_transaction.raw_protobuf_len())
+                    {
+                        dropped_transactions += 1;
+                        continue;
+                    }
                     self.handle_received_transaction(raw_transaction, &propagation_source);
                 }
 
                 // Handle control messages
                 let mut have_tx_ids = Vec::new();
                 let mut reset_route = false;
+                let mut dropped_control_messages = 0;
                 for control_msg in rpc.control_msgs {
+                    if !self
+                        .rate_limiter(propagation_source)
+                        .allow_control_message()
+                    {
+                        dropped_control_messages += 1;
+                        continue;
+                    }
                     match control_msg {
                         ControlAction::HaveTx(have_tx) => {
                             have_tx_ids.push(have_tx.tx_id);
@@ -705,6 +1759,15 @@ where
                         ControlAction::ResetRoute(_) => {
                             reset_route = true;
                         }
+                        ControlAction::SyncRequest(sync_request) => {
+                            self.handle_sync_request(sync_request.tx_ids, &propagation_source);
+                        }
+                        ControlAction::SyncSummary(sync_summary) => {
+                            self.handle_sync_summary(sync_summary.tx_ids, &propagation_source);
+                        }
+                        ControlAction::Reconcile(reconcile) => {
+                            self.handle_reconcile(reconcile.sketch, &propagation_source);
+                        }
                     }
                 }
                 if !have_tx_ids.is_empty() {
@@ -713,6 +1776,14 @@ where
                 if reset_route {
                     self.handle_reset_route(&propagation_source);
                 }
+
+                if dropped_transactions > 0 || dropped_control_messages > 0 {
+                    self.handle_rate_limited(
+                        propagation_source,
+                        dropped_transactions,
+                        dropped_control_messages,
+                    );
+                }
             }
             HandlerEvent::TransactionDropped(rpc) => {
                 tracing::warn!(
@@ -729,6 +1800,31 @@ where
                     }
                     m.register_timedout_tx_dropped();
                 }
+
+                if let RpcOut::Publish { tx_id, .. } = rpc {
+                    if let Some(outcome) = self.publish_outcomes.dropped(&tx_id, propagation_source)
+                    {
+                        self.emit_publish_outcome(outcome);
+                    }
+                }
+            }
+            HandlerEvent::TransactionSent(tx_id) => {
+                if let Some(outcome) = self.publish_outcomes.sent(&tx_id, propagation_source) {
+                    self.emit_publish_outcome(outcome);
+                }
+            }
+            HandlerEvent::ProtocolSupported => {
+                let first_protocol_peer = !self.is_protocol_peer_connected();
+                let Some(peer) = self.connected_peers.get_mut(&propagation_source) else {
+                    return;
+                };
+                peer.protocol_supported = true;
+
+                tracing::debug!(peer=%propagation_source, "Peer supports the DOG protocol");
+
+                if first_protocol_peer {
+                    self.flush_publish_buffer();
+                }
             }
         }
     }
@@ -738,17 +1834,84 @@ where
         &mut self,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<ToSwarm<Self::ToSwarm, libp2p::swarm::THandlerInEvent<Self>>> {
+        // Requests are left in the bounded queue while too many are pending, so that publishers
+        // wait for room. The pending publishes are completed before taking new requests, and the
+        // requests are taken again as long as completing publishes frees room for them, so that
+        // the receiver is always polled when there is room.
+        let mut requests = 0;
+        loop {
+            while let Poll::Ready(Some((raw_transaction, data, response))) =
+                self.pending_publishes.poll_next_unpin(cx)
+            {
+                let result = raw_transaction.and_then(|raw_transaction| {
+                    self.publish_raw_transaction(raw_transaction, data)
+                });
+                // The requester may have dropped the future, in which case the outcome is ignored.
+                let _ = response.send(result);
+            }
+
+            let mut progress = false;
+            while self.pending_publishes.len() < self.config.publish_request_queue_len() {
+                if requests == MAX_PUBLISH_REQUESTS_PER_POLL {
+                    cx.waker().wake_by_ref();
+                    break;
+                }
+                let Poll::Ready(Some(request)) = self.publish_receiver.poll_next_unpin(cx) else {
+                    break;
+                };
+                self.handle_publish_request(request);
+                requests += 1;
+                progress = true;
+            }
+
+            if !progress || requests == MAX_PUBLISH_REQUESTS_PER_POLL {
+                break;
+            }
+        }
+
+        for outcome in self.publish_outcomes.remove_expired() {
+            self.emit_publish_outcome(outcome);
+        }
+
+        if self.cache.remove_expired() > 0 {
+            self.update_cache_metrics();
+        }
+
+        self.known_transactions.remove_expired();
+
+        self.fast_transaction_ids.remove_expired();
+        // The fast ids are not reported in the metrics.
+        self.fast_transaction_ids.take_removed();
+
+        while let Poll::Ready(Some(tx_id)) = self.publish_retries.poll_next_unpin(cx) {
+            self.retry_publish(tx_id);
+        }
+
+        for tx_id in self.publish_buffer.remove_expired() {
+            self.emit_publish_expired(tx_id);
+        }
+
         if let Some(event) = self.events.pop_front() {
             return Poll::Ready(event);
         }
 
         // TODO: the check might be first priority and be done before the event check
-        if self.redundancy_interval.poll_unpin(cx).is_ready() {
+        // The timers are polled again after being reset so that they wake the task when they
+        // next fire.
+        while self.redundancy_interval.poll_unpin(cx).is_ready() {
             self.adjust_redundancy();
             self.redundancy_interval
                 .reset(self.config.redundancy_interval());
         }
 
+        while let Some(reconcile_interval) = self.reconcile_interval.as_mut() {
+            if reconcile_interval.poll_unpin(cx).is_pending() {
+                break;
+            }
+            reconcile_interval.reset(self.config.reconcile_interval().unwrap_or_default());
+            self.reconcile();
+        }
+
         Poll::Pending
     }
 
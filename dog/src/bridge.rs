@@ -0,0 +1,189 @@
+use std::time::Duration;
+
+use libp2p::{
+    gossipsub::{self, IdentTopic, MessageId, TopicSubscriptionFilter},
+    PeerId,
+};
+
+use crate::{
+    behaviour::{Behaviour, Event},
+    error::PublishError,
+    time_cache::DuplicateCache,
+    transform::DataTransform,
+    types::{PublishOptions, Transaction, TransactionId},
+};
+
+/// The header carrying the id of the gossipsub message a transaction was bridged from.
+const MESSAGE_ID_HEADER: &str = "gossipsub-message-id";
+/// The header carrying the source of the gossipsub message a transaction was bridged from.
+const SOURCE_HEADER: &str = "gossipsub-source";
+/// The header carrying the sequence number of the gossipsub message a transaction was bridged
+/// from, in big-endian.
+const SEQNO_HEADER: &str = "gossipsub-seqno";
+
+/// Republishes the transactions received through DOG onto a gossipsub topic, and the gossipsub
+/// messages received on that topic through DOG, allowing to migrate gossipsub consumers
+/// incrementally.
+///
+/// The bridge is driven by the application, which passes it the events of both behaviours. Bridged
+/// transactions are republished by the local node, so they get its author and a new sequence
+/// number in the target protocol. Transactions bridged from gossipsub carry the id, source and
+/// sequence number of the original message as headers, mapped back with
+/// [`GossipsubBridge::original_transaction`]. Gossipsub messages have no room for such metadata,
+/// so messages bridged from DOG only carry the data of the transaction.
+///
+/// To prevent loops, the bridge remembers the [`TransactionId`] and [`MessageId`] of the bridged
+/// transactions and messages for the given time and does not bridge them again, and transactions
+/// bridged from gossipsub are never bridged back to gossipsub. Several bridges may run on the same
+/// network. A transaction published on DOG is then bridged to gossipsub by each of them, and each
+/// resulting message comes back to DOG once through each of the other bridges.
+pub struct GossipsubBridge {
+    /// The gossipsub topic transactions are bridged to and from.
+    topic: IdentTopic,
+    /// The ids of the recently bridged transactions, received or published.
+    bridged_transactions: DuplicateCache<TransactionId, ()>,
+    /// The ids of the recently bridged gossipsub messages, received or published.
+    bridged_messages: DuplicateCache<MessageId, ()>,
+}
+
+impl GossipsubBridge {
+    /// Creates a bridge between DOG and the given gossipsub topic, remembering bridged
+    /// transactions and messages for `ttl`. The local node should be subscribed to the topic.
+    pub fn new(topic: IdentTopic, ttl: Duration) -> Self {
+        GossipsubBridge {
+            topic,
+            bridged_transactions: DuplicateCache::new(ttl, None),
+            bridged_messages: DuplicateCache::new(ttl, None),
+        }
+    }
+
+    pub fn topic(&self) -> &IdentTopic {
+        &self.topic
+    }
+
+    /// Returns the id of the gossipsub message a transaction was bridged from, if any.
+    pub fn bridged_message_id(transaction: &Transaction) -> Option<MessageId> {
+        transaction
+            .headers
+            .get(MESSAGE_ID_HEADER)
+            .map(|id| MessageId::new(id))
+    }
+
+    /// Maps a transaction bridged from gossipsub back to the author and sequence number of the
+    /// original message, removing the headers added by the bridge. Other transactions are
+    /// returned unchanged.
+    pub fn original_transaction(transaction: &Transaction) -> Transaction {
+        let mut transaction = transaction.clone();
+        if transaction.headers.remove(MESSAGE_ID_HEADER).is_none() {
+            return transaction;
+        }
+
+        transaction.from = transaction
+            .headers
+            .remove(SOURCE_HEADER)
+            .and_then(|source| PeerId::from_bytes(&source).ok());
+        transaction.seqno = transaction
+            .headers
+            .remove(SEQNO_HEADER)
+            .and_then(|seqno| seqno.try_into().ok())
+            .map(u64::from_be_bytes);
+        transaction
+    }
+
+    /// Handles an event of the DOG behaviour, republishing received transactions on the gossipsub
+    /// topic. Returns the id of the published gossipsub message, or `None` if the event is not a
+    /// transaction or the transaction has already been bridged.
+    pub fn on_dog_event<D, F>(
+        &mut self,
+        event: &Event,
+        gossipsub: &mut gossipsub::Behaviour<D, F>,
+    ) -> Result<Option<MessageId>, gossipsub::PublishError>
+    where
+        D: gossipsub::DataTransform + Send + 'static,
+        F: TopicSubscriptionFilter + Send + 'static,
+    {
+        let Event::Transaction {
+            transaction_id,
+            transaction,
+            ..
+        } = event
+        else {
+            return Ok(None);
+        };
+
+        if Self::bridged_message_id(transaction).is_some() {
+            tracing::trace!("Transaction bridged from gossipsub, ignoring");
+            return Ok(None);
+        }
+
+        if !self.bridged_transactions.insert(transaction_id.clone(), ()) {
+            tracing::trace!("Transaction already bridged, ignoring");
+            return Ok(None);
+        }
+
+        match gossipsub.publish(self.topic.clone(), transaction.data.clone()) {
+            Ok(message_id) => {
+                tracing::debug!(message=%message_id, "Bridged transaction to gossipsub");
+                self.bridged_messages.insert(message_id.clone(), ());
+                Ok(Some(message_id))
+            }
+            Err(gossipsub::PublishError::Duplicate) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Handles an event of the gossipsub behaviour, republishing the messages received on the
+    /// topic through DOG. Returns the id of the published transaction, or `None` if the event is
+    /// not a message of the topic or the message has already been bridged.
+    pub fn on_gossipsub_event<D>(
+        &mut self,
+        event: &gossipsub::Event,
+        dog: &mut Behaviour<D>,
+    ) -> Result<Option<TransactionId>, PublishError>
+    where
+        D: DataTransform + Send + Sync + 'static,
+    {
+        let gossipsub::Event::Message {
+            message_id,
+            message,
+            ..
+        } = event
+        else {
+            return Ok(None);
+        };
+
+        if message.topic != self.topic.hash() {
+            return Ok(None);
+        }
+
+        if !self.bridged_messages.insert(message_id.clone(), ()) {
+            tracing::trace!("Message already bridged, ignoring");
+            return Ok(None);
+        }
+
+        let mut options = PublishOptions::default();
+        options
+            .headers
+            .insert(MESSAGE_ID_HEADER.to_string(), message_id.0.clone());
+        if let Some(source) = message.source {
+            options
+                .headers
+                .insert(SOURCE_HEADER.to_string(), source.to_bytes());
+        }
+        if let Some(seqno) = message.sequence_number {
+            options
+                .headers
+                .insert(SEQNO_HEADER.to_string(), seqno.to_be_bytes().to_vec());
+        }
+
+        match dog.publish_with_options(message.data.clone(), options) {
+            Ok(tx_id) => {
+                tracing::debug!(transaction=%tx_id, "Bridged gossipsub message to DOG");
+                self.bridged_transactions.insert(tx_id.clone(), ());
+                Ok(Some(tx_id))
+            }
+            Err(PublishError::Duplicate) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
@@ -1,27 +1,123 @@
 use std::{sync::Arc, time::Duration};
 
+use libp2p::PeerId;
+use sha2::{Digest, Sha256};
+
 use crate::{
+    iblt,
     protocol::ProtocolConfig,
-    types::{Transaction, TransactionId},
+    types::{RawTransaction, Transaction, TransactionId},
 };
 
 /// The types of transaction validation that can be employed by dog.
 #[derive(Debug, Clone)]
 pub enum ValidationMode {
-    /// This is the default setting. This settings validates all fields of the transaction.
+    /// This is the default setting. This requires the transaction author to be a valid [`PeerId`]
+    /// and to be present as well as the sequence number. All transactions must have valid
+    /// signatures.
+    ///
+    /// NOTE: This setting will reject transactions from nodes using
+    /// [`crate::behaviour::TransactionAuthenticity::Anonymous`] and all transactions that do not
+    /// have signatures.
     Strict,
-    /// This setting only checks that the author field is valid
+    /// This setting permits transactions that have no author, sequence number or signature. If any
+    /// of these fields exist in the transaction these are validated.
+    Permissive,
+    /// This setting requires the author, sequence number and signature fields of a transaction to
+    /// be empty. Any transaction that contains these fields is considered invalid.
+    Anonymous,
+    /// This setting requires the author to be a valid [`PeerId`] and the sequence number to be
+    /// present. The signature field is not checked.
+    Author,
+    /// This setting only checks that the author is a valid [`PeerId`]. The sequence number and
+    /// signature fields of incoming transactions are not checked, if they contain data they are
+    /// simply passed to the application.
+    ///
+    /// NOTE: This setting will consider transactions with invalid signatures as valid transactions
+    /// and will reject transactions from nodes using
+    /// [`crate::behaviour::TransactionAuthenticity::Anonymous`].
     None,
 }
 
+/// The data structures that can be used by dog to detect duplicate transactions.
+#[derive(Debug, Clone)]
+pub enum DuplicateFilterMode {
+    /// This is the default setting. The ids of the transactions seen during
+    /// [`Config::cache_time`] are stored in an exact cache, along with the peer each transaction
+    /// was received from. The number of ids can be bounded with [`Config::cache_capacity`].
+    Exact,
+    /// The ids of the transactions are stored in two rotating Bloom filters, using a fixed amount
+    /// of memory. A new transaction is wrongly considered as a duplicate with a probability of
+    /// about `false_positive_rate`, and is then neither delivered nor forwarded.
+    ///
+    /// NOTE: The peer each transaction was received from, needed to handle `HaveTx` messages, is
+    /// only kept for the last `source_window` transactions.
+    Bloom {
+        /// The number of transactions expected during [`Config::cache_time`]. The filters are
+        /// rotated early when it is exceeded.
+        capacity: usize,
+        /// The target false-positive rate, between 0 and 1.
+        false_positive_rate: f64,
+        /// The number of recent transactions for which the source peer is kept.
+        source_window: usize,
+    },
+}
+
+/// The built-in functions computing the id of a transaction. See
+/// [`ConfigBuilder::transaction_id_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionIdMode {
+    /// This is the default setting. The id is the author followed by the sequence number. If
+    /// either of them is not provided, it is set to 0.
+    AuthorSeqno,
+    /// The id is the SHA-256 hash of the data, so identical transactions published by different
+    /// authors have the same id.
+    Sha256,
+    /// The id is the BLAKE3 hash of the data, so identical transactions published by different
+    /// authors have the same id. It is faster to compute than SHA-256.
+    Blake3,
+    /// The id is the author followed by the SHA-256 hash of the data. If the author is not
+    /// provided, it is set to 0.
+    AuthorHash,
+}
+
+impl TransactionIdMode {
+    /// Computes the id of a transaction.
+    pub fn transaction_id(&self, tx: &Transaction) -> TransactionId {
+        // NOTE: If the author is not provided, we set it to 0.
+        let from = tx
+            .from
+            .unwrap_or_else(|| PeerId::from_bytes(&[0, 1, 0]).expect("Valid peer id"));
+
+        match self {
+            TransactionIdMode::AuthorSeqno => {
+                let mut from_string = from.to_base58();
+                from_string.push_str(&tx.seqno.unwrap_or_default().to_string());
+                TransactionId::from(from_string)
+            }
+            TransactionIdMode::Sha256 => TransactionId::from(Sha256::digest(&tx.data).to_vec()),
+            TransactionIdMode::Blake3 => TransactionId::new(blake3::hash(&tx.data).as_bytes()),
+            TransactionIdMode::AuthorHash => {
+                let mut id = from.to_bytes();
+                id.extend_from_slice(&Sha256::digest(&tx.data));
+                TransactionId::from(id)
+            }
+        }
+    }
+}
+
 /// Configuration parameters that define the performance of the dog network.
 #[derive(Clone)]
 pub struct Config {
     protocol: ProtocolConfig,
     transaction_id_fn: Arc<dyn Fn(&Transaction) -> TransactionId + Send + Sync + 'static>,
+    fast_transaction_id_fn:
+        Option<Arc<dyn Fn(&RawTransaction) -> TransactionId + Send + Sync + 'static>>,
     max_transactions_per_rpc: Option<usize>,
     connection_handler_queue_len: usize,
     cache_time: Duration,
+    cache_capacity: Option<usize>,
+    duplicate_filter_mode: DuplicateFilterMode,
     target_redundancy: f64,
     redundancy_delta_percent: u8,
     redundancy_interval: Duration,
@@ -29,6 +125,32 @@ pub struct Config {
     connection_handler_forward_duration: Duration,
     deliver_own_transactions: bool,
     forward_transactions: bool,
+    replay_window: Option<usize>,
+    replay_author_ttl: Duration,
+    replay_max_authors: usize,
+    published_transactions_capacity: usize,
+    publish_max_retries: usize,
+    publish_retry_delay: Duration,
+    max_pending_publishes: usize,
+    pending_publish_ttl: Duration,
+    max_stored_transactions: usize,
+    max_stored_bytes: usize,
+    sync_on_connection: bool,
+    max_sync_transactions: usize,
+    sync_min_interval: Duration,
+    reconcile_interval: Option<Duration>,
+    reconcile_sketch_size: usize,
+    peer_max_transactions_per_second: Option<f64>,
+    peer_max_bytes_per_second: Option<f64>,
+    peer_max_control_messages_per_second: Option<f64>,
+    reset_route_cooldown: Duration,
+    max_reset_routes_per_interval: usize,
+    max_disabled_routes_per_peer: Option<usize>,
+    max_known_transactions_per_peer: Option<usize>,
+    min_active_routes_per_peer: usize,
+    priority_class_weights: Vec<u32>,
+    max_forwarded_priority: Option<u32>,
+    publish_request_queue_len: usize,
 }
 
 impl Config {
@@ -42,6 +164,23 @@ impl Config {
         (self.transaction_id_fn)(tx)
     }
 
+    /// A user-defined optional function computing a fast id of a transaction as received on the
+    /// wire, before the inbound [`crate::DataTransform`]. The transactions whose fast id has
+    /// already been seen are handled as duplicates without being transformed, e.g. decompressed,
+    /// and without computing their transaction id. Transactions with the same fast id must have
+    /// the same transaction id. The default is `None`.
+    pub fn fast_transaction_id(&self, raw_transaction: &RawTransaction) -> Option<TransactionId> {
+        self.fast_transaction_id_fn
+            .as_ref()
+            .map(|fast_transaction_id_fn| fast_transaction_id_fn(raw_transaction))
+    }
+
+    /// Determines the level of validation used when receiving transactions. See [`ValidationMode`]
+    /// for the available types. The default is `ValidationMode::Strict`.
+    pub fn validation_mode(&self) -> &ValidationMode {
+        &self.protocol.validation_mode
+    }
+
     /// The maximum number of transactions we will process in a given RPC. If this is unset, there is
     /// no limit. The default is `None`.
     pub fn max_transactions_per_rpc(&self) -> Option<usize> {
@@ -58,6 +197,21 @@ impl Config {
         self.cache_time
     }
 
+    /// The maximum number of transaction ids stored in the cache. When it is reached, the oldest
+    /// ids are evicted before they expire, which may lead to duplicate transactions being
+    /// delivered. If this is unset, the cache is only bounded by [`Config::cache_time`]. The
+    /// default is `None`.
+    pub fn cache_capacity(&self) -> Option<usize> {
+        self.cache_capacity
+    }
+
+    /// Determines the data structure used to detect duplicate transactions. See
+    /// [`DuplicateFilterMode`] for the available types. The default is
+    /// `DuplicateFilterMode::Exact`.
+    pub fn duplicate_filter_mode(&self) -> &DuplicateFilterMode {
+        &self.duplicate_filter_mode
+    }
+
     /// The target redundancy for the network. The default is 1.0.
     pub fn target_redundancy(&self) -> f64 {
         self.target_redundancy
@@ -100,6 +254,203 @@ impl Config {
     pub fn forward_transactions(&self) -> bool {
         self.forward_transactions
     }
+
+    /// The number of sequence numbers tracked per author to detect replayed transactions. A
+    /// transaction is rejected if its sequence number has already been seen from the same author
+    /// or is older than the highest one seen minus this window. If this is unset, replay
+    /// protection is disabled. The default is `None`.
+    pub fn replay_window(&self) -> Option<usize> {
+        self.replay_window
+    }
+
+    /// The time the sequence numbers of an author are tracked after its last transaction. The
+    /// default is 10 minutes.
+    pub fn replay_author_ttl(&self) -> Duration {
+        self.replay_author_ttl
+    }
+
+    /// The maximum number of authors whose sequence numbers are tracked. When it is reached, the
+    /// least recently seen author is forgotten, and its old transactions are then only rejected
+    /// while they are in the duplicate cache. The default is 10000.
+    pub fn replay_max_authors(&self) -> usize {
+        self.replay_max_authors
+    }
+
+    /// The number of our own published transactions kept to be republished with
+    /// [`crate::Behaviour::republish`]. The default is 1024.
+    pub fn published_transactions_capacity(&self) -> usize {
+        self.published_transactions_capacity
+    }
+
+    /// The number of times a published transaction that has been sent to no peer, e.g. because its
+    /// sends timed out, is automatically republished. Publishing fails without any retry when no
+    /// peer can be sent the transaction, see [`Config::max_pending_publishes`]. The default is 0,
+    /// i.e. no automatic retry.
+    pub fn publish_max_retries(&self) -> usize {
+        self.publish_max_retries
+    }
+
+    /// The delay before automatically republishing a transaction that could not be sent to any
+    /// peer. The default is 1 second.
+    pub fn publish_retry_delay(&self) -> Duration {
+        self.publish_retry_delay
+    }
+
+    /// The maximum number of transactions published while no peer supporting the DOG protocol is
+    /// connected that are buffered until such a peer connects. When the buffer is full, the oldest
+    /// transaction is dropped. The default is 0, i.e. publishing fails with
+    /// [`crate::PublishError::InsufficientPeers`] when no peer is connected, and the transaction
+    /// can be published again.
+    pub fn max_pending_publishes(&self) -> usize {
+        self.max_pending_publishes
+    }
+
+    /// The time a transaction can wait in the pending publish buffer before it is dropped. The
+    /// default is 30 seconds.
+    pub fn pending_publish_ttl(&self) -> Duration {
+        self.pending_publish_ttl
+    }
+
+    /// The maximum number of recently seen transactions kept in the transaction store, to be
+    /// looked up with [`crate::Behaviour::get_transaction`]. Transactions remain in the store for
+    /// [`Config::cache_time`]. The default is 0, i.e. the store is disabled.
+    pub fn max_stored_transactions(&self) -> usize {
+        self.max_stored_transactions
+    }
+
+    /// The maximum number of data bytes kept in the transaction store. The default is 16 MiB.
+    pub fn max_stored_bytes(&self) -> usize {
+        self.max_stored_bytes
+    }
+
+    /// Whether the node should catch up with the recent transactions of a peer when it connects
+    /// to it. The peer responds with a summary of the transactions in its transaction store, see
+    /// [`Config::max_stored_transactions`], and the missing ones are fetched. The default is
+    /// `false`.
+    pub fn sync_on_connection(&self) -> bool {
+        self.sync_on_connection
+    }
+
+    /// The maximum number of transaction ids in a sync summary, and of transactions fetched in a
+    /// single sync. The default is 1000.
+    pub fn max_sync_transactions(&self) -> usize {
+        self.max_sync_transactions
+    }
+
+    /// The minimum time between two sync summaries served to the same peer. Sync requests
+    /// received more often are ignored. Transactions fetched during a sync that have not been
+    /// received within this time are treated as routed transactions. The default is 10 seconds.
+    pub fn sync_min_interval(&self) -> Duration {
+        self.sync_min_interval
+    }
+
+    /// Time between each reconciliation of the transaction store with our peers. Each peer is sent
+    /// a sketch of our recent transactions, from which it computes the ones we are missing, which
+    /// are then fetched as in a sync. The interval should be larger than
+    /// [`Config::sync_min_interval`], as peers ignore reconciliations received more often. If
+    /// this is unset, reconciliation is disabled and the reconciliations received from peers are
+    /// ignored. The default is `None`.
+    pub fn reconcile_interval(&self) -> Option<Duration> {
+        self.reconcile_interval
+    }
+
+    /// The number of cells of the sketches sent for reconciliation. A sketch can recover a
+    /// difference of up to about half its number of cells. It must be at least 3, the number of
+    /// cells each transaction is inserted into. Sketches received with more cells are ignored. The
+    /// default is 96.
+    pub fn reconcile_sketch_size(&self) -> usize {
+        self.reconcile_sketch_size
+    }
+
+    /// The maximum number of transactions per second accepted from a single peer, with bursts of up
+    /// to one second worth of transactions. Excess transactions are dropped. If this is unset,
+    /// there is no limit. The default is `None`.
+    pub fn peer_max_transactions_per_second(&self) -> Option<f64> {
+        self.peer_max_transactions_per_second
+    }
+
+    /// The maximum number of transaction bytes per second accepted from a single peer, with bursts
+    /// of up to one second worth of bytes. Excess transactions are dropped, so this should be
+    /// larger than [`Config::max_transmit_size`]. If this is unset, there is no limit. The default
+    /// is `None`.
+    pub fn peer_max_bytes_per_second(&self) -> Option<f64> {
+        self.peer_max_bytes_per_second
+    }
+
+    /// The maximum number of control messages (e.g. `HaveTx` or `ResetRoute`) per second accepted
+    /// from a single peer, with bursts of up to one second worth of messages. Excess messages are
+    /// dropped. If this is unset, there is no limit. The default is `None`.
+    pub fn peer_max_control_messages_per_second(&self) -> Option<f64> {
+        self.peer_max_control_messages_per_second
+    }
+
+    /// The minimum time between two routes re-enabled at the request of the same peer. `ResetRoute`
+    /// messages received from a peer within this time of the last honoured one are ignored. The
+    /// default is 500 milliseconds.
+    pub fn reset_route_cooldown(&self) -> Duration {
+        self.reset_route_cooldown
+    }
+
+    /// The maximum number of routes re-enabled following `ResetRoute` messages per
+    /// [`Config::redundancy_interval`], across all peers. Further `ResetRoute` messages are ignored
+    /// until the next interval. The default is 10.
+    pub fn max_reset_routes_per_interval(&self) -> usize {
+        self.max_reset_routes_per_interval
+    }
+
+    /// The maximum number of disabled routes to a single peer. `HaveTx` messages that would disable
+    /// more routes to the peer are ignored, so that it keeps receiving transactions from other
+    /// sources. If this is unset, all the routes to a peer can be disabled. The default is `None`.
+    pub fn max_disabled_routes_per_peer(&self) -> Option<usize> {
+        self.max_disabled_routes_per_peer
+    }
+
+    /// The maximum number of transaction ids remembered per peer, separately for the transactions
+    /// sent to and received from the peer, for at most [`Config::cache_time`]. They are used to
+    /// avoid forwarding transactions to peers known to have them and to ignore `HaveTx` messages
+    /// for transactions never sent to the peer. `HaveTx` messages for transactions that may have
+    /// been evicted before they expired are ignored without being reported as a misbehaviour. If
+    /// this is unset, the ids are only bounded by [`Config::cache_time`]. The default is `None`.
+    pub fn max_known_transactions_per_peer(&self) -> Option<usize> {
+        self.max_known_transactions_per_peer
+    }
+
+    /// The minimum number of active routes to a single peer. A peer has a route from each of the
+    /// other connected peers and from the local node. `HaveTx` messages that would leave the peer
+    /// with fewer active routes are ignored, so that it does not rely on `ResetRoute` messages to
+    /// keep receiving transactions. The default is 1.
+    pub fn min_active_routes_per_peer(&self) -> usize {
+        self.min_active_routes_per_peer
+    }
+
+    /// The weights of the transaction priority classes, indexed by class. The transactions queued
+    /// to a peer are sent with weighted fair queuing between the classes, each class getting a
+    /// share of the bandwidth proportional to its weight, so that bulk traffic cannot starve
+    /// urgent transactions. Transactions of an unknown class use the last class. Control messages
+    /// are always sent first. The default is a single class.
+    ///
+    /// NOTE: The priority class is chosen by the author of each transaction, so any publisher can
+    /// claim the most favoured class. See [`Config::max_forwarded_priority`] to limit the class of
+    /// forwarded transactions.
+    pub fn priority_class_weights(&self) -> &[u32] {
+        &self.priority_class_weights
+    }
+
+    /// The highest priority class in which transactions received from peers are forwarded.
+    /// Forwarded transactions of a higher class are queued in this class, while their priority is
+    /// kept unchanged on the wire, as it is signed. With weights increasing with the class, this
+    /// keeps the classes above it for the transactions published by the local node. If this is
+    /// unset, forwarded transactions keep their class. The default is `None`.
+    pub fn max_forwarded_priority(&self) -> Option<u32> {
+        self.max_forwarded_priority
+    }
+
+    /// The maximum number of publish requests from [`crate::DogPublisher`] handles that are queued
+    /// or being transformed and signed. Once it is reached, [`crate::DogPublisher::publish`] waits
+    /// for room before queuing the transaction. The default is 1024.
+    pub fn publish_request_queue_len(&self) -> usize {
+        self.publish_request_queue_len
+    }
 }
 
 impl Default for Config {
@@ -120,15 +471,14 @@ impl Default for ConfigBuilder {
         Self {
             config: Config {
                 protocol: ProtocolConfig::default(),
-                transaction_id_fn: Arc::new(|tx| {
-                    // default transaction id is: source + sequence number
-                    let mut from_string = tx.from.to_base58();
-                    from_string.push_str(&tx.seqno.to_string());
-                    TransactionId::from(from_string)
-                }),
+                // default transaction id is: source + sequence number
+                transaction_id_fn: Arc::new(|tx| TransactionIdMode::AuthorSeqno.transaction_id(tx)),
+                fast_transaction_id_fn: None,
                 max_transactions_per_rpc: None,
                 connection_handler_queue_len: 5000,
                 cache_time: Duration::from_secs(30),
+                cache_capacity: None,
+                duplicate_filter_mode: DuplicateFilterMode::Exact,
                 target_redundancy: 1.0,
                 redundancy_delta_percent: 10,
                 redundancy_interval: Duration::from_secs(1),
@@ -136,6 +486,32 @@ impl Default for ConfigBuilder {
                 connection_handler_forward_duration: Duration::from_secs(1),
                 deliver_own_transactions: false,
                 forward_transactions: true,
+                replay_window: None,
+                replay_author_ttl: Duration::from_secs(600),
+                replay_max_authors: 10000,
+                published_transactions_capacity: 1024,
+                publish_max_retries: 0,
+                publish_retry_delay: Duration::from_secs(1),
+                max_pending_publishes: 0,
+                pending_publish_ttl: Duration::from_secs(30),
+                max_stored_transactions: 0,
+                max_stored_bytes: 16 * 1024 * 1024,
+                sync_on_connection: false,
+                max_sync_transactions: 1000,
+                sync_min_interval: Duration::from_secs(10),
+                reconcile_interval: None,
+                reconcile_sketch_size: 96,
+                peer_max_transactions_per_second: None,
+                peer_max_bytes_per_second: None,
+                peer_max_control_messages_per_second: None,
+                reset_route_cooldown: Duration::from_millis(500),
+                max_reset_routes_per_interval: 10,
+                max_disabled_routes_per_peer: None,
+                max_known_transactions_per_peer: None,
+                min_active_routes_per_peer: 1,
+                priority_class_weights: vec![1],
+                max_forwarded_priority: None,
+                publish_request_queue_len: 1024,
             },
         }
     }
@@ -150,6 +526,10 @@ impl From<Config> for ConfigBuilder {
 impl ConfigBuilder {
     /// A user-defined function allowing the user to specify the transaction id of a dog transaction.
     /// The default value is to concatenate the source peer id with a sequence number.
+    ///
+    /// NOTE: Transactions published with [`crate::TransactionAuthenticity::Anonymous`] carry
+    /// neither an author nor a sequence number, so a custom function is required to prevent them
+    /// from all being filtered as duplicates.
     pub fn transaction_id_fn<F>(&mut self, transaction_id_fn: F) -> &mut Self
     where
         F: Fn(&Transaction) -> TransactionId + Send + Sync + 'static,
@@ -158,6 +538,27 @@ impl ConfigBuilder {
         self
     }
 
+    /// Selects one of the built-in functions computing the transaction id of a dog transaction.
+    /// See [`TransactionIdMode`] for the available functions. This replaces any function set with
+    /// [`ConfigBuilder::transaction_id_fn`]. The default is `TransactionIdMode::AuthorSeqno`.
+    pub fn transaction_id_mode(&mut self, transaction_id_mode: TransactionIdMode) -> &mut Self {
+        self.config.transaction_id_fn = Arc::new(move |tx| transaction_id_mode.transaction_id(tx));
+        self
+    }
+
+    /// A user-defined optional function computing a fast id of a transaction as received on the
+    /// wire, before the inbound [`crate::DataTransform`]. The transactions whose fast id has
+    /// already been seen are handled as duplicates without being transformed, e.g. decompressed,
+    /// and without computing their transaction id. Transactions with the same fast id must have
+    /// the same transaction id. The default is `None`.
+    pub fn fast_transaction_id_fn<F>(&mut self, fast_transaction_id_fn: F) -> &mut Self
+    where
+        F: Fn(&RawTransaction) -> TransactionId + Send + Sync + 'static,
+    {
+        self.config.fast_transaction_id_fn = Some(Arc::new(fast_transaction_id_fn));
+        self
+    }
+
     /// The maximum number of transactions we will process in a given RPC. If this is unset, there is
     /// no limit. The default is `None`.
     pub fn max_transactions_per_rpc(&mut self, max_transactions_per_rpc: usize) -> &mut Self {
@@ -180,6 +581,26 @@ impl ConfigBuilder {
         self
     }
 
+    /// The maximum number of transaction ids stored in the cache. When it is reached, the oldest
+    /// ids are evicted before they expire, which may lead to duplicate transactions being
+    /// delivered. If this is unset, the cache is only bounded by [`Config::cache_time`]. The
+    /// default is `None`.
+    pub fn cache_capacity(&mut self, cache_capacity: usize) -> &mut Self {
+        self.config.cache_capacity = Some(cache_capacity);
+        self
+    }
+
+    /// Determines the data structure used to detect duplicate transactions. See
+    /// [`DuplicateFilterMode`] for the available types. The default is
+    /// `DuplicateFilterMode::Exact`.
+    pub fn duplicate_filter_mode(
+        &mut self,
+        duplicate_filter_mode: DuplicateFilterMode,
+    ) -> &mut Self {
+        self.config.duplicate_filter_mode = duplicate_filter_mode;
+        self
+    }
+
     /// The target redundancy for the network. The default is 1.0.
     pub fn target_redundancy(&mut self, target_redundancy: f64) -> &mut Self {
         self.config.target_redundancy = target_redundancy;
@@ -237,8 +658,250 @@ impl ConfigBuilder {
         self
     }
 
+    /// The number of sequence numbers tracked per author to detect replayed transactions. A
+    /// transaction is rejected if its sequence number has already been seen from the same author
+    /// or is older than the highest one seen minus this window. If this is unset, replay
+    /// protection is disabled. The default is `None`.
+    pub fn replay_window(&mut self, replay_window: usize) -> &mut Self {
+        self.config.replay_window = Some(replay_window);
+        self
+    }
+
+    /// The time the sequence numbers of an author are tracked after its last transaction. The
+    /// default is 10 minutes.
+    pub fn replay_author_ttl(&mut self, replay_author_ttl: Duration) -> &mut Self {
+        self.config.replay_author_ttl = replay_author_ttl;
+        self
+    }
+
+    /// The maximum number of authors whose sequence numbers are tracked. When it is reached, the
+    /// least recently seen author is forgotten, and its old transactions are then only rejected
+    /// while they are in the duplicate cache. The default is 10000.
+    pub fn replay_max_authors(&mut self, replay_max_authors: usize) -> &mut Self {
+        self.config.replay_max_authors = replay_max_authors;
+        self
+    }
+
+    /// The number of our own published transactions kept to be republished with
+    /// [`crate::Behaviour::republish`]. The default is 1024.
+    pub fn published_transactions_capacity(
+        &mut self,
+        published_transactions_capacity: usize,
+    ) -> &mut Self {
+        self.config.published_transactions_capacity = published_transactions_capacity;
+        self
+    }
+
+    /// The number of times a published transaction that has been sent to no peer, e.g. because its
+    /// sends timed out, is automatically republished. Publishing fails without any retry when no
+    /// peer can be sent the transaction, see [`Config::max_pending_publishes`]. The default is 0,
+    /// i.e. no automatic retry.
+    pub fn publish_max_retries(&mut self, publish_max_retries: usize) -> &mut Self {
+        self.config.publish_max_retries = publish_max_retries;
+        self
+    }
+
+    /// The delay before automatically republishing a transaction that could not be sent to any
+    /// peer. The default is 1 second.
+    pub fn publish_retry_delay(&mut self, publish_retry_delay: Duration) -> &mut Self {
+        self.config.publish_retry_delay = publish_retry_delay;
+        self
+    }
+
+    /// The maximum number of transactions published while no peer supporting the DOG protocol is
+    /// connected that are buffered until such a peer connects. When the buffer is full, the oldest
+    /// transaction is dropped. The default is 0, i.e. publishing fails with
+    /// [`crate::PublishError::InsufficientPeers`] when no peer is connected, and the transaction
+    /// can be published again.
+    pub fn max_pending_publishes(&mut self, max_pending_publishes: usize) -> &mut Self {
+        self.config.max_pending_publishes = max_pending_publishes;
+        self
+    }
+
+    /// The time a transaction can wait in the pending publish buffer before it is dropped. The
+    /// default is 30 seconds.
+    pub fn pending_publish_ttl(&mut self, pending_publish_ttl: Duration) -> &mut Self {
+        self.config.pending_publish_ttl = pending_publish_ttl;
+        self
+    }
+
+    /// The maximum number of recently seen transactions kept in the transaction store, to be
+    /// looked up with [`crate::Behaviour::get_transaction`]. Transactions remain in the store for
+    /// [`Config::cache_time`]. The default is 0, i.e. the store is disabled.
+    pub fn max_stored_transactions(&mut self, max_stored_transactions: usize) -> &mut Self {
+        self.config.max_stored_transactions = max_stored_transactions;
+        self
+    }
+
+    /// The maximum number of data bytes kept in the transaction store. The default is 16 MiB.
+    pub fn max_stored_bytes(&mut self, max_stored_bytes: usize) -> &mut Self {
+        self.config.max_stored_bytes = max_stored_bytes;
+        self
+    }
+
+    /// Whether the node should catch up with the recent transactions of a peer when it connects
+    /// to it. The peer responds with a summary of the transactions in its transaction store, see
+    /// [`Config::max_stored_transactions`], and the missing ones are fetched. The default is
+    /// `false`.
+    pub fn sync_on_connection(&mut self, sync_on_connection: bool) -> &mut Self {
+        self.config.sync_on_connection = sync_on_connection;
+        self
+    }
+
+    /// The maximum number of transaction ids in a sync summary, and of transactions fetched in a
+    /// single sync. The default is 1000.
+    pub fn max_sync_transactions(&mut self, max_sync_transactions: usize) -> &mut Self {
+        self.config.max_sync_transactions = max_sync_transactions;
+        self
+    }
+
+    /// The minimum time between two sync summaries served to the same peer. Sync requests
+    /// received more often are ignored. Transactions fetched during a sync that have not been
+    /// received within this time are treated as routed transactions. The default is 10 seconds.
+    pub fn sync_min_interval(&mut self, sync_min_interval: Duration) -> &mut Self {
+        self.config.sync_min_interval = sync_min_interval;
+        self
+    }
+
+    /// Time between each reconciliation of the transaction store with our peers. Each peer is sent
+    /// a sketch of our recent transactions, from which it computes the ones we are missing, which
+    /// are then fetched as in a sync. The interval should be larger than
+    /// [`Config::sync_min_interval`], as peers ignore reconciliations received more often. If
+    /// this is unset, reconciliation is disabled and the reconciliations received from peers are
+    /// ignored. The default is `None`.
+    pub fn reconcile_interval(&mut self, reconcile_interval: Duration) -> &mut Self {
+        self.config.reconcile_interval = Some(reconcile_interval);
+        self
+    }
+
+    /// The number of cells of the sketches sent for reconciliation. A sketch can recover a
+    /// difference of up to about half its number of cells. It must be at least 3, the number of
+    /// cells each transaction is inserted into. Sketches received with more cells are ignored. The
+    /// default is 96.
+    pub fn reconcile_sketch_size(&mut self, reconcile_sketch_size: usize) -> &mut Self {
+        self.config.reconcile_sketch_size = reconcile_sketch_size;
+        self
+    }
+
+    /// The maximum number of transactions per second accepted from a single peer, with bursts of up
+    /// to one second worth of transactions. Excess transactions are dropped. If this is unset,
+    /// there is no limit. The default is `None`.
+    pub fn peer_max_transactions_per_second(
+        &mut self,
+        peer_max_transactions_per_second: f64,
+    ) -> &mut Self {
+        self.config.peer_max_transactions_per_second = Some(peer_max_transactions_per_second);
+        self
+    }
+
+    /// The maximum number of transaction bytes per second accepted from a single peer, with bursts
+    /// of up to one second worth of bytes. Excess transactions are dropped, so this should be
+    /// larger than [`Config::max_transmit_size`]. If this is unset, there is no limit. The default
+    /// is `None`.
+    pub fn peer_max_bytes_per_second(&mut self, peer_max_bytes_per_second: f64) -> &mut Self {
+        self.config.peer_max_bytes_per_second = Some(peer_max_bytes_per_second);
+        self
+    }
+
+    /// The maximum number of control messages (e.g. `HaveTx` or `ResetRoute`) per second accepted
+    /// from a single peer, with bursts of up to one second worth of messages. Excess messages are
+    /// dropped. If this is unset, there is no limit. The default is `None`.
+    pub fn peer_max_control_messages_per_second(
+        &mut self,
+        peer_max_control_messages_per_second: f64,
+    ) -> &mut Self {
+        self.config.peer_max_control_messages_per_second =
+            Some(peer_max_control_messages_per_second);
+        self
+    }
+
+    /// The minimum time between two routes re-enabled at the request of the same peer. `ResetRoute`
+    /// messages received from a peer within this time of the last honoured one are ignored. The
+    /// default is 500 milliseconds.
+    pub fn reset_route_cooldown(&mut self, reset_route_cooldown: Duration) -> &mut Self {
+        self.config.reset_route_cooldown = reset_route_cooldown;
+        self
+    }
+
+    /// The maximum number of routes re-enabled following `ResetRoute` messages per
+    /// [`Config::redundancy_interval`], across all peers. Further `ResetRoute` messages are ignored
+    /// until the next interval. The default is 10.
+    pub fn max_reset_routes_per_interval(
+        &mut self,
+        max_reset_routes_per_interval: usize,
+    ) -> &mut Self {
+        self.config.max_reset_routes_per_interval = max_reset_routes_per_interval;
+        self
+    }
+
+    /// The maximum number of disabled routes to a single peer. `HaveTx` messages that would disable
+    /// more routes to the peer are ignored, so that it keeps receiving transactions from other
+    /// sources. If this is unset, all the routes to a peer can be disabled. The default is `None`.
+    pub fn max_disabled_routes_per_peer(
+        &mut self,
+        max_disabled_routes_per_peer: usize,
+    ) -> &mut Self {
+        self.config.max_disabled_routes_per_peer = Some(max_disabled_routes_per_peer);
+        self
+    }
+
+    /// The maximum number of transaction ids remembered per peer, separately for the transactions
+    /// sent to and received from the peer, for at most [`Config::cache_time`]. They are used to
+    /// avoid forwarding transactions to peers known to have them and to ignore `HaveTx` messages
+    /// for transactions never sent to the peer. `HaveTx` messages for transactions that may have
+    /// been evicted before they expired are ignored without being reported as a misbehaviour. If
+    /// this is unset, the ids are only bounded by [`Config::cache_time`]. The default is `None`.
+    pub fn max_known_transactions_per_peer(
+        &mut self,
+        max_known_transactions_per_peer: usize,
+    ) -> &mut Self {
+        self.config.max_known_transactions_per_peer = Some(max_known_transactions_per_peer);
+        self
+    }
+
+    /// The minimum number of active routes to a single peer. A peer has a route from each of the
+    /// other connected peers and from the local node. `HaveTx` messages that would leave the peer
+    /// with fewer active routes are ignored, so that it does not rely on `ResetRoute` messages to
+    /// keep receiving transactions. The default is 1.
+    pub fn min_active_routes_per_peer(&mut self, min_active_routes_per_peer: usize) -> &mut Self {
+        self.config.min_active_routes_per_peer = min_active_routes_per_peer;
+        self
+    }
+
+    /// The weights of the transaction priority classes, indexed by class. The transactions queued
+    /// to a peer are sent with weighted fair queuing between the classes, each class getting a
+    /// share of the bandwidth proportional to its weight, so that bulk traffic cannot starve
+    /// urgent transactions. Transactions of an unknown class use the last class. Control messages
+    /// are always sent first. The default is a single class.
+    ///
+    /// NOTE: The priority class is chosen by the author of each transaction, so any publisher can
+    /// claim the most favoured class. See [`Config::max_forwarded_priority`] to limit the class of
+    /// forwarded transactions.
+    pub fn priority_class_weights(&mut self, priority_class_weights: Vec<u32>) -> &mut Self {
+        self.config.priority_class_weights = priority_class_weights;
+        self
+    }
+
+    /// The highest priority class in which transactions received from peers are forwarded.
+    /// Forwarded transactions of a higher class are queued in this class, while their priority is
+    /// kept unchanged on the wire, as it is signed. With weights increasing with the class, this
+    /// keeps the classes above it for the transactions published by the local node. If this is
+    /// unset, forwarded transactions keep their class. The default is `None`.
+    pub fn max_forwarded_priority(&mut self, max_forwarded_priority: u32) -> &mut Self {
+        self.config.max_forwarded_priority = Some(max_forwarded_priority);
+        self
+    }
+
+    /// The maximum number of publish requests from [`crate::DogPublisher`] handles that are queued
+    /// or being transformed and signed. Once it is reached, [`crate::DogPublisher::publish`] waits
+    /// for room before queuing the transaction. The default is 1024.
+    pub fn publish_request_queue_len(&mut self, publish_request_queue_len: usize) -> &mut Self {
+        self.config.publish_request_queue_len = publish_request_queue_len;
+        self
+    }
+
     /// Determines the level of validation used when receiving transactions. See [`ValidationMode`]
-    /// for the available types. the default is `ValidationMode::Strict`.
+    /// for the available types. The default is `ValidationMode::Strict`.
     pub fn validation_mode(&mut self, validation_mode: ValidationMode) -> &mut Self {
         self.config.protocol.validation_mode = validation_mode;
         self
@@ -246,8 +909,86 @@ impl ConfigBuilder {
 
     /// Constructs a `Config` from the parameters set in the builder.
     pub fn build(&self) -> Result<Config, &'static str> {
-        // TODO: validate config
+        if self.config.replay_window == Some(0) {
+            return Err("The replay window must be positive");
+        }
+
+        if self.config.replay_max_authors == 0 {
+            return Err("The maximum number of replay tracked authors must be positive");
+        }
+
+        if let DuplicateFilterMode::Bloom {
+            capacity,
+            false_positive_rate,
+            ..
+        } = self.config.duplicate_filter_mode
+        {
+            if capacity == 0 {
+                return Err("The Bloom filter capacity must be positive");
+            }
+
+            if !(false_positive_rate > 0.0 && false_positive_rate < 1.0) {
+                return Err("The Bloom filter false-positive rate must be between 0 and 1");
+            }
+        }
+
+        if self.config.reconcile_sketch_size < iblt::HASH_COUNT {
+            return Err("The reconciliation sketch size must be at least 3");
+        }
+
+        if self.config.priority_class_weights.is_empty()
+            || self.config.priority_class_weights.contains(&0)
+        {
+            return Err("At least one priority class is required and weights must be positive");
+        }
+
+        if self.config.publish_request_queue_len == 0 {
+            return Err("The publish request queue length must be positive");
+        }
+
+        // The bursts are one second worth of tokens, so a rate below 1 admits nothing.
+        if [
+            self.config.peer_max_transactions_per_second,
+            self.config.peer_max_bytes_per_second,
+            self.config.peer_max_control_messages_per_second,
+        ]
+        .into_iter()
+        .flatten()
+        .any(|rate| rate.is_nan() || rate < 1.0)
+        {
+            return Err("The peer rate limits must be at least 1 per second");
+        }
 
         Ok(self.config.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_rate_limits() {
+        for rate in [0.0, 0.5, -1.0, f64::NAN] {
+            assert!(ConfigBuilder::default()
+                .peer_max_transactions_per_second(rate)
+                .build()
+                .is_err());
+            assert!(ConfigBuilder::default()
+                .peer_max_bytes_per_second(rate)
+                .build()
+                .is_err());
+            assert!(ConfigBuilder::default()
+                .peer_max_control_messages_per_second(rate)
+                .build()
+                .is_err());
+        }
+
+        assert!(ConfigBuilder::default()
+            .peer_max_transactions_per_second(1.0)
+            .peer_max_bytes_per_second(65536.0)
+            .peer_max_control_messages_per_second(10.0)
+            .build()
+            .is_ok());
+    }
+}
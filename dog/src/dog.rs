@@ -1,7 +1,12 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    time::Duration,
+};
 
 use libp2p::PeerId;
 use rand::seq::IteratorRandom;
+use web_time::Instant;
 
 use crate::Config;
 
@@ -31,16 +36,72 @@ impl Display for Route {
     }
 }
 
+/// Behaviour of a peer that may be an attempt to manipulate the routing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Misbehaviour {
+    /// The peer sent `HaveTx` messages for transactions we never sent to it.
+    UnsolicitedHaveTx {
+        /// The number of transaction ids ignored.
+        transactions: usize,
+    },
+    /// The peer sent a `ResetRoute` message although we never disabled a route to it.
+    UnsolicitedResetRoute,
+}
+
+/// The reasons a route cannot be disabled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum DisableRouteRejection {
+    /// The target already has [`Config::max_disabled_routes_per_peer`] disabled routes.
+    MaxDisabledRoutes,
+    /// Disabling the route would leave the target with less than
+    /// [`Config::min_active_routes_per_peer`] active routes.
+    MinActiveRoutes,
+}
+
+/// The reasons a ResetRoute request is not honoured.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ResetRouteRejection {
+    /// We never disabled a route to the requesting peer.
+    NeverDisabled,
+    /// The requesting peer has no disabled route left.
+    NoDisabledRoute,
+    /// The requesting peer sent a ResetRoute too soon after the previous one.
+    Cooldown,
+    /// The maximum number of routes re-enabled during the current interval has been reached.
+    IntervalCapReached,
+}
+
 pub(crate) struct Router {
     disabled_routes: Vec<Route>,
     have_tx_sent_per_peer: HashMap<PeerId, usize>,
+    /// Peers we have disabled at least one route to since they connected.
+    routes_disabled_to: HashSet<PeerId>,
+    /// The last time a route was re-enabled at the request of each peer.
+    last_reset_route: HashMap<PeerId, Instant>,
+    /// The minimum time between two routes re-enabled at the request of the same peer.
+    reset_route_cooldown: Duration,
+    /// The maximum number of routes re-enabled per interval.
+    max_reset_routes_per_interval: usize,
+    /// The number of routes re-enabled during the current interval.
+    reset_routes_count: usize,
+    /// The maximum number of disabled routes to a single peer.
+    max_disabled_routes_per_peer: Option<usize>,
+    /// The minimum number of active routes to a single peer.
+    min_active_routes_per_peer: usize,
 }
 
 impl Router {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(config: &Config) -> Self {
         Router {
             disabled_routes: Vec::new(),
             have_tx_sent_per_peer: HashMap::new(),
+            routes_disabled_to: HashSet::new(),
+            last_reset_route: HashMap::new(),
+            reset_route_cooldown: config.reset_route_cooldown(),
+            max_reset_routes_per_interval: config.max_reset_routes_per_interval(),
+            reset_routes_count: 0,
+            max_disabled_routes_per_peer: config.max_disabled_routes_per_peer(),
+            min_active_routes_per_peer: config.min_active_routes_per_peer(),
         }
     }
 
@@ -48,11 +109,80 @@ impl Router {
         self.disabled_routes.clone()
     }
 
-    pub(crate) fn disable_route(&mut self, source: PeerId, target: PeerId) {
-        self.disabled_routes.push(Route::new(source, target));
+    /// Disables the route from `source` to `target`.
+    ///
+    /// The routes to `target` are the ones from each of the other `connected_peers` and from the
+    /// local node, so `target` has `connected_peers` routes when none of them is disabled.
+    pub(crate) fn disable_route(
+        &mut self,
+        source: PeerId,
+        target: PeerId,
+        connected_peers: usize,
+    ) -> Result<(), DisableRouteRejection> {
+        let route = Route::new(source, target);
+        if self.disabled_routes.contains(&route) {
+            return Ok(());
+        }
+
+        let disabled_to_target = self
+            .disabled_routes
+            .iter()
+            .filter(|route| route.target() == &target)
+            .count();
+
+        if self
+            .max_disabled_routes_per_peer
+            .is_some_and(|max| disabled_to_target >= max)
+        {
+            return Err(DisableRouteRejection::MaxDisabledRoutes);
+        }
+
+        let active_routes = connected_peers.saturating_sub(disabled_to_target);
+        if active_routes <= self.min_active_routes_per_peer {
+            return Err(DisableRouteRejection::MinActiveRoutes);
+        }
+
+        self.disabled_routes.push(route);
+        self.routes_disabled_to.insert(target);
+        Ok(())
     }
 
-    pub(crate) fn enable_random_route_to_peer(&mut self, peer: PeerId) -> Option<Route> {
+    /// Re-enables a random route to `peer` at its request, unless the request is rejected by the
+    /// ResetRoute safeguards.
+    pub(crate) fn reset_route(&mut self, peer: PeerId) -> Result<Route, ResetRouteRejection> {
+        if !self.routes_disabled_to.contains(&peer) {
+            return Err(ResetRouteRejection::NeverDisabled);
+        }
+
+        let now = Instant::now();
+        if self
+            .last_reset_route
+            .get(&peer)
+            .is_some_and(|last| now < *last + self.reset_route_cooldown)
+        {
+            return Err(ResetRouteRejection::Cooldown);
+        }
+
+        if self.reset_routes_count >= self.max_reset_routes_per_interval {
+            return Err(ResetRouteRejection::IntervalCapReached);
+        }
+
+        let route = self
+            .enable_random_route_to_peer(peer)
+            .ok_or(ResetRouteRejection::NoDisabledRoute)?;
+
+        self.last_reset_route.insert(peer, now);
+        self.reset_routes_count += 1;
+
+        Ok(route)
+    }
+
+    /// Starts a new interval for the maximum number of routes re-enabled per interval.
+    pub(crate) fn reset_interval(&mut self) {
+        self.reset_routes_count = 0;
+    }
+
+    fn enable_random_route_to_peer(&mut self, peer: PeerId) -> Option<Route> {
         if let Some((index, _)) = self
             .disabled_routes
             .iter()
@@ -66,6 +196,9 @@ impl Router {
     }
 
     pub(crate) fn reset_routes_with_peer(&mut self, peer: PeerId) -> Vec<Route> {
+        self.routes_disabled_to.remove(&peer);
+        self.last_reset_route.remove(&peer);
+
         let mut removed_routes = Vec::new();
         self.disabled_routes.retain(|route| {
             if route.source() == &peer || route.target() == &peer {
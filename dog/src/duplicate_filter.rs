@@ -0,0 +1,241 @@
+use std::{hash::Hasher, time::Duration};
+
+use fnv::FnvHasher;
+use libp2p::PeerId;
+use web_time::Instant;
+
+use crate::{time_cache::DuplicateCache, types::TransactionId};
+
+/// A filter of the recently seen transaction ids, used to detect duplicate transactions and to
+/// remember the peer each transaction was first received from.
+pub(crate) trait DuplicateFilter: Send {
+    /// Inserts a transaction id along with the peer it was received from. Returns `false` if the
+    /// id was already in the filter.
+    fn insert(&mut self, tx_id: TransactionId, source: PeerId) -> bool;
+
+    /// Returns whether the transaction id is in the filter.
+    fn contains(&self, tx_id: &TransactionId) -> bool;
+
+    /// Returns the peer the transaction was first received from, if it is still known.
+    fn source(&self, tx_id: &TransactionId) -> Option<PeerId>;
+
+    /// Removes the expired transaction ids from the filter and returns their number.
+    fn remove_expired(&mut self) -> usize;
+
+    /// The number of transaction ids in the filter.
+    fn len(&self) -> usize;
+
+    /// An estimate of the memory used by the filter, in bytes.
+    fn memory(&self) -> usize;
+
+    /// Returns the number of ids expired and evicted since the last call, and resets them.
+    fn take_removed(&mut self) -> (usize, usize);
+}
+
+impl DuplicateFilter for DuplicateCache<TransactionId, PeerId> {
+    fn insert(&mut self, tx_id: TransactionId, source: PeerId) -> bool {
+        DuplicateCache::insert(self, tx_id, source)
+    }
+
+    fn contains(&self, tx_id: &TransactionId) -> bool {
+        DuplicateCache::contains(self, tx_id)
+    }
+
+    fn source(&self, tx_id: &TransactionId) -> Option<PeerId> {
+        self.get(tx_id).copied()
+    }
+
+    fn remove_expired(&mut self) -> usize {
+        DuplicateCache::remove_expired(self)
+    }
+
+    fn len(&self) -> usize {
+        DuplicateCache::len(self)
+    }
+
+    fn memory(&self) -> usize {
+        DuplicateCache::memory(self)
+    }
+
+    fn take_removed(&mut self) -> (usize, usize) {
+        DuplicateCache::take_removed(self)
+    }
+}
+
+/// A fixed-size Bloom filter of transaction ids.
+struct BloomFilter {
+    bits: Vec<u64>,
+    /// The number of bits of the filter.
+    num_bits: u64,
+    /// The number of bits set per transaction id.
+    num_hashes: u32,
+    /// The number of transaction ids inserted.
+    len: usize,
+}
+
+impl BloomFilter {
+    /// Creates a Bloom filter sized for `capacity` transaction ids with the given false-positive
+    /// rate.
+    fn new(capacity: usize, false_positive_rate: f64) -> Self {
+        let capacity = capacity.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = (-capacity * false_positive_rate.ln() / (ln2 * ln2))
+            .ceil()
+            .max(64.0) as u64;
+        let num_hashes = ((num_bits as f64 / capacity) * ln2).round().max(1.0) as u32;
+
+        BloomFilter {
+            bits: vec![0; num_bits.div_ceil(64) as usize],
+            num_bits,
+            num_hashes,
+            len: 0,
+        }
+    }
+
+    /// Returns the indices of the bits of a transaction id, using double hashing.
+    fn indices(&self, tx_id: &TransactionId) -> impl Iterator<Item = u64> + '_ {
+        let mut hasher = FnvHasher::default();
+        hasher.write(&tx_id.0);
+        let h1 = hasher.finish();
+
+        let mut hasher = FnvHasher::with_key(0x9e37_79b9_7f4a_7c15);
+        hasher.write(&tx_id.0);
+        let h2 = hasher.finish() | 1;
+
+        (0..self.num_hashes as u64)
+            .map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % self.num_bits)
+    }
+
+    fn contains(&self, tx_id: &TransactionId) -> bool {
+        self.indices(tx_id)
+            .all(|index| self.bits[(index / 64) as usize] & (1 << (index % 64)) != 0)
+    }
+
+    fn insert(&mut self, tx_id: &TransactionId) {
+        let indices = self.indices(tx_id).collect::<Vec<_>>();
+        for index in indices {
+            self.bits[(index / 64) as usize] |= 1 << (index % 64);
+        }
+        self.len += 1;
+    }
+
+    fn clear(&mut self) {
+        self.bits.iter_mut().for_each(|word| *word = 0);
+        self.len = 0;
+    }
+
+    fn memory(&self) -> usize {
+        self.bits.len() * std::mem::size_of::<u64>()
+    }
+}
+
+/// A duplicate filter backed by two rotating Bloom filters, using a fixed amount of memory
+/// regardless of the transaction rate.
+///
+/// New ids are inserted in the current filter. Every `ttl`, or when the current filter is full,
+/// the previous filter is cleared and the two are swapped, so ids remain in the filter between
+/// one and two rotations. The peer the transactions were received from is only kept for the most
+/// recent ids, in an exact cache.
+pub(crate) struct RotatingBloomFilter {
+    current: BloomFilter,
+    previous: BloomFilter,
+    /// The number of ids each filter is sized for.
+    capacity: usize,
+    /// The time between two rotations.
+    ttl: Duration,
+    /// The time of the next rotation.
+    next_rotation: Instant,
+    /// The peer each of the most recent transactions was received from.
+    sources: DuplicateCache<TransactionId, PeerId>,
+    /// Number of ids expired since the last call to `take_removed`.
+    expired: usize,
+    /// Number of ids evicted since the last call to `take_removed`.
+    evicted: usize,
+}
+
+impl RotatingBloomFilter {
+    /// Creates a filter sized for `capacity` ids per `ttl`, with an overall false-positive rate of
+    /// about `false_positive_rate`. The source peer is kept for the last `source_window` ids.
+    pub(crate) fn new(
+        capacity: usize,
+        false_positive_rate: f64,
+        source_window: usize,
+        ttl: Duration,
+    ) -> Self {
+        // An id is looked up in both filters, so each of them gets half of the false-positive rate.
+        let false_positive_rate = false_positive_rate / 2.0;
+
+        RotatingBloomFilter {
+            current: BloomFilter::new(capacity, false_positive_rate),
+            previous: BloomFilter::new(capacity, false_positive_rate),
+            capacity,
+            ttl,
+            next_rotation: Instant::now() + ttl,
+            sources: DuplicateCache::new(ttl, Some(source_window)),
+            expired: 0,
+            evicted: 0,
+        }
+    }
+
+    fn rotate(&mut self) -> usize {
+        let removed = self.previous.len;
+        self.previous.clear();
+        std::mem::swap(&mut self.current, &mut self.previous);
+        self.next_rotation = Instant::now() + self.ttl;
+        removed
+    }
+}
+
+impl DuplicateFilter for RotatingBloomFilter {
+    fn insert(&mut self, tx_id: TransactionId, source: PeerId) -> bool {
+        self.remove_expired();
+
+        if self.contains(&tx_id) {
+            return false;
+        }
+
+        if self.current.len >= self.capacity {
+            self.evicted += self.rotate();
+        }
+
+        self.current.insert(&tx_id);
+        self.sources.insert(tx_id, source);
+        true
+    }
+
+    fn contains(&self, tx_id: &TransactionId) -> bool {
+        self.current.contains(tx_id) || self.previous.contains(tx_id)
+    }
+
+    fn source(&self, tx_id: &TransactionId) -> Option<PeerId> {
+        self.sources.get(tx_id).copied()
+    }
+
+    fn remove_expired(&mut self) -> usize {
+        self.sources.remove_expired();
+        // The source window is bounded by the filters, its removals are not reported.
+        self.sources.take_removed();
+
+        if Instant::now() < self.next_rotation {
+            return 0;
+        }
+        let expired = self.rotate();
+        self.expired += expired;
+        expired
+    }
+
+    fn len(&self) -> usize {
+        self.current.len + self.previous.len
+    }
+
+    fn memory(&self) -> usize {
+        self.current.memory() + self.previous.memory() + self.sources.memory()
+    }
+
+    fn take_removed(&mut self) -> (usize, usize) {
+        (
+            std::mem::take(&mut self.expired),
+            std::mem::take(&mut self.evicted),
+        )
+    }
+}
@@ -6,6 +6,8 @@ pub enum PublishError {
     Duplicate,
     /// An error occurred while signing the transaction.
     SigningError(SigningError),
+    /// The custom [`crate::TransactionSigner`] failed to sign the transaction.
+    SignerFailed(std::io::Error),
     /// There were no peers to send this transaction to.
     InsufficientPeers,
     /// The overal transaction was too large.
@@ -15,6 +17,13 @@ pub enum PublishError {
     /// Transaction could not be sent because the queues for all peers were full. The usize represents
     /// the number of peers that were attempted.
     AllQueuesFull(usize),
+    /// The behaviour was dropped before the transaction could be published.
+    BehaviourDropped,
+    /// The transaction to republish is not in the store of published transactions.
+    UnknownTransaction,
+    /// Transactions signed by a [`crate::TransactionSigner`] must be published with a
+    /// [`crate::DogPublisher`], so that the signer does not block the swarm task.
+    AsyncSigner,
 }
 
 impl std::fmt::Display for PublishError {
@@ -26,7 +35,7 @@ impl std::fmt::Display for PublishError {
 impl std::error::Error for PublishError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            Self::TransformFailed(err) => Some(err),
+            Self::TransformFailed(err) | Self::SignerFailed(err) => Some(err),
             _ => None,
         }
     }
@@ -44,12 +53,30 @@ impl From<SigningError> for PublishError {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum ValidationError {
     /// The PeerId was invalid.
     InvalidPeerId,
     /// The signature was invalid.
     InvalidSignature,
+    /// The signature was empty, expected a value.
+    MissingSignature,
+    /// The sequence number was empty, expected a value.
+    EmptySequenceNumber,
+    /// Signature existed when validation has been set to [`crate::ValidationMode::Anonymous`].
+    SignaturePresent,
+    /// Sequence number existed when validation has been set to
+    /// [`crate::ValidationMode::Anonymous`].
+    SequenceNumberPresent,
+    /// Transaction author existed when validation has been set to
+    /// [`crate::ValidationMode::Anonymous`].
+    AuthorPresent,
+    /// The sequence number has already been seen from this author or is too old to be tracked.
+    Replay,
+    /// The data transformation failed.
+    TransformFailed,
+    /// The headers were not sorted by key or contained a duplicate key.
+    InvalidHeaders,
 }
 
 impl std::fmt::Display for ValidationError {
@@ -57,6 +57,10 @@ pub struct Transaction {
     pub data: Vec<u8>,
     pub signature: Vec<u8>,
     pub key: Vec<u8>,
+    pub headers: Vec<dog::pb::TransactionHeader>,
+    pub hops: u32,
+    pub ttl: u32,
+    pub priority: u32,
 }
 
 impl<'a> MessageRead<'a> for Transaction {
@@ -69,6 +73,10 @@ impl<'a> MessageRead<'a> for Transaction {
                 Ok(26) => msg.data = r.read_bytes(bytes)?.to_owned(),
                 Ok(34) => msg.signature = r.read_bytes(bytes)?.to_owned(),
                 Ok(42) => msg.key = r.read_bytes(bytes)?.to_owned(),
+                Ok(50) => msg.headers.push(r.read_message::<dog::pb::TransactionHeader>(bytes)?),
+                Ok(56) => msg.hops = r.read_uint32(bytes)?,
+                Ok(64) => msg.ttl = r.read_uint32(bytes)?,
+                Ok(72) => msg.priority = r.read_uint32(bytes)?,
                 Ok(t) => { r.read_unknown(bytes, t)?; }
                 Err(e) => return Err(e),
             }
@@ -85,6 +93,10 @@ impl MessageWrite for Transaction {
         + if self.data.is_empty() { 0 } else { 1 + sizeof_len((&self.data).len()) }
         + if self.signature.is_empty() { 0 } else { 1 + sizeof_len((&self.signature).len()) }
         + if self.key.is_empty() { 0 } else { 1 + sizeof_len((&self.key).len()) }
+        + self.headers.iter().map(|s| 1 + sizeof_len((s).get_size())).sum::<usize>()
+        + if self.hops == 0u32 { 0 } else { 1 + sizeof_varint(*(&self.hops) as u64) }
+        + if self.ttl == 0u32 { 0 } else { 1 + sizeof_varint(*(&self.ttl) as u64) }
+        + if self.priority == 0u32 { 0 } else { 1 + sizeof_varint(*(&self.priority) as u64) }
     }
 
     fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
@@ -93,6 +105,46 @@ impl MessageWrite for Transaction {
         if !self.data.is_empty() { w.write_with_tag(26, |w| w.write_bytes(&**&self.data))?; }
         if !self.signature.is_empty() { w.write_with_tag(34, |w| w.write_bytes(&**&self.signature))?; }
         if !self.key.is_empty() { w.write_with_tag(42, |w| w.write_bytes(&**&self.key))?; }
+        for s in &self.headers { w.write_with_tag(50, |w| w.write_message(s))?; }
+        if self.hops != 0u32 { w.write_with_tag(56, |w| w.write_uint32(*&self.hops))?; }
+        if self.ttl != 0u32 { w.write_with_tag(64, |w| w.write_uint32(*&self.ttl))?; }
+        if self.priority != 0u32 { w.write_with_tag(72, |w| w.write_uint32(*&self.priority))?; }
+        Ok(())
+    }
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct TransactionHeader {
+    pub key: String,
+    pub value: Vec<u8>,
+}
+
+impl<'a> MessageRead<'a> for TransactionHeader {
+    fn from_reader(r: &mut BytesReader, bytes: &'a [u8]) -> Result<Self> {
+        let mut msg = Self::default();
+        while !r.is_eof() {
+            match r.next_tag(bytes) {
+                Ok(10) => msg.key = r.read_string(bytes)?.to_owned(),
+                Ok(18) => msg.value = r.read_bytes(bytes)?.to_owned(),
+                Ok(t) => { r.read_unknown(bytes, t)?; }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(msg)
+    }
+}
+
+impl MessageWrite for TransactionHeader {
+    fn get_size(&self) -> usize {
+        0
+        + if self.key == String::default() { 0 } else { 1 + sizeof_len((&self.key).len()) }
+        + if self.value.is_empty() { 0 } else { 1 + sizeof_len((&self.value).len()) }
+    }
+
+    fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
+        if self.key != String::default() { w.write_with_tag(10, |w| w.write_string(&**&self.key))?; }
+        if !self.value.is_empty() { w.write_with_tag(18, |w| w.write_bytes(&**&self.value))?; }
         Ok(())
     }
 }
@@ -102,6 +154,9 @@ impl MessageWrite for Transaction {
 pub struct ControlMessage {
     pub have_tx: Vec<dog::pb::ControlHaveTx>,
     pub reset_route: Vec<dog::pb::ControlResetRoute>,
+    pub sync_request: Vec<dog::pb::ControlSyncRequest>,
+    pub sync_summary: Vec<dog::pb::ControlSyncSummary>,
+    pub reconcile: Vec<dog::pb::ControlReconcile>,
 }
 
 impl<'a> MessageRead<'a> for ControlMessage {
@@ -111,6 +166,9 @@ impl<'a> MessageRead<'a> for ControlMessage {
             match r.next_tag(bytes) {
                 Ok(10) => msg.have_tx.push(r.read_message::<dog::pb::ControlHaveTx>(bytes)?),
                 Ok(18) => msg.reset_route.push(r.read_message::<dog::pb::ControlResetRoute>(bytes)?),
+                Ok(26) => msg.sync_request.push(r.read_message::<dog::pb::ControlSyncRequest>(bytes)?),
+                Ok(34) => msg.sync_summary.push(r.read_message::<dog::pb::ControlSyncSummary>(bytes)?),
+                Ok(42) => msg.reconcile.push(r.read_message::<dog::pb::ControlReconcile>(bytes)?),
                 Ok(t) => { r.read_unknown(bytes, t)?; }
                 Err(e) => return Err(e),
             }
@@ -124,11 +182,17 @@ impl MessageWrite for ControlMessage {
         0
         + self.have_tx.iter().map(|s| 1 + sizeof_len((s).get_size())).sum::<usize>()
         + self.reset_route.iter().map(|s| 1 + sizeof_len((s).get_size())).sum::<usize>()
+        + self.sync_request.iter().map(|s| 1 + sizeof_len((s).get_size())).sum::<usize>()
+        + self.sync_summary.iter().map(|s| 1 + sizeof_len((s).get_size())).sum::<usize>()
+        + self.reconcile.iter().map(|s| 1 + sizeof_len((s).get_size())).sum::<usize>()
     }
 
     fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
         for s in &self.have_tx { w.write_with_tag(10, |w| w.write_message(s))?; }
         for s in &self.reset_route { w.write_with_tag(18, |w| w.write_message(s))?; }
+        for s in &self.sync_request { w.write_with_tag(26, |w| w.write_message(s))?; }
+        for s in &self.sync_summary { w.write_with_tag(34, |w| w.write_message(s))?; }
+        for s in &self.reconcile { w.write_with_tag(42, |w| w.write_message(s))?; }
         Ok(())
     }
 }
@@ -178,3 +242,99 @@ impl<'a> MessageRead<'a> for ControlResetRoute {
 
 impl MessageWrite for ControlResetRoute { }
 
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ControlSyncRequest {
+    pub tx_ids: Vec<Vec<u8>>,
+}
+
+impl<'a> MessageRead<'a> for ControlSyncRequest {
+    fn from_reader(r: &mut BytesReader, bytes: &'a [u8]) -> Result<Self> {
+        let mut msg = Self::default();
+        while !r.is_eof() {
+            match r.next_tag(bytes) {
+                Ok(10) => msg.tx_ids.push(r.read_bytes(bytes)?.to_owned()),
+                Ok(t) => { r.read_unknown(bytes, t)?; }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(msg)
+    }
+}
+
+impl MessageWrite for ControlSyncRequest {
+    fn get_size(&self) -> usize {
+        0
+        + self.tx_ids.iter().map(|s| 1 + sizeof_len((s).len())).sum::<usize>()
+    }
+
+    fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
+        for s in &self.tx_ids { w.write_with_tag(10, |w| w.write_bytes(&**s))?; }
+        Ok(())
+    }
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ControlSyncSummary {
+    pub tx_ids: Vec<Vec<u8>>,
+}
+
+impl<'a> MessageRead<'a> for ControlSyncSummary {
+    fn from_reader(r: &mut BytesReader, bytes: &'a [u8]) -> Result<Self> {
+        let mut msg = Self::default();
+        while !r.is_eof() {
+            match r.next_tag(bytes) {
+                Ok(10) => msg.tx_ids.push(r.read_bytes(bytes)?.to_owned()),
+                Ok(t) => { r.read_unknown(bytes, t)?; }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(msg)
+    }
+}
+
+impl MessageWrite for ControlSyncSummary {
+    fn get_size(&self) -> usize {
+        0
+        + self.tx_ids.iter().map(|s| 1 + sizeof_len((s).len())).sum::<usize>()
+    }
+
+    fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
+        for s in &self.tx_ids { w.write_with_tag(10, |w| w.write_bytes(&**s))?; }
+        Ok(())
+    }
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ControlReconcile {
+    pub sketch: Vec<u8>,
+}
+
+impl<'a> MessageRead<'a> for ControlReconcile {
+    fn from_reader(r: &mut BytesReader, bytes: &'a [u8]) -> Result<Self> {
+        let mut msg = Self::default();
+        while !r.is_eof() {
+            match r.next_tag(bytes) {
+                Ok(10) => msg.sketch = r.read_bytes(bytes)?.to_owned(),
+                Ok(t) => { r.read_unknown(bytes, t)?; }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(msg)
+    }
+}
+
+impl MessageWrite for ControlReconcile {
+    fn get_size(&self) -> usize {
+        0
+        + if self.sketch.is_empty() { 0 } else { 1 + sizeof_len((&self.sketch).len()) }
+    }
+
+    fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> Result<()> {
+        if !self.sketch.is_empty() { w.write_with_tag(10, |w| w.write_bytes(&**&self.sketch))?; }
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    collections::VecDeque,
     future::Future,
     pin::Pin,
     task::{Context, Poll},
@@ -23,7 +24,7 @@ use crate::{
     protocol::{DogCodec, ProtocolConfig},
     rpc::Receiver,
     rpc_proto::proto,
-    types::{RawTransaction, Rpc, RpcOut},
+    types::{RawTransaction, Rpc, RpcOut, TransactionId},
 };
 
 /// The event emitted by the Handler. This informs the behaviour of various events created
@@ -41,6 +42,11 @@ pub enum HandlerEvent {
     },
     /// A transaction to be published was dropped because it could not be sent in time.
     TransactionDropped(RpcOut),
+    /// A published transaction has been written to the outbound substream.
+    TransactionSent(TransactionId),
+    /// The remote peer supports the DOG protocol. Reported once, when the first substream is
+    /// negotiated.
+    ProtocolSupported,
 }
 
 /// A message sent from the behaviour to the handler.
@@ -76,6 +82,13 @@ pub struct EnabledHandler {
     outbound_substream_attempts: usize,
     /// The number of inbound substream attempts.
     inbound_substream_attempts: usize,
+    /// The ids of the published transactions written to the outbound substream that have not
+    /// been reported to the behaviour yet.
+    sent_transactions: VecDeque<TransactionId>,
+    /// Whether a substream has been negotiated with the remote peer.
+    protocol_supported: bool,
+    /// Whether the behaviour has been told that the remote peer supports the protocol.
+    protocol_supported_sent: bool,
 }
 
 pub enum DisabledHandler {
@@ -101,10 +114,11 @@ enum InboundSubstreamState {
 enum OutboundSubstreamState {
     /// Waiting for a new transaction to send to the remote. This is the idle state.
     WaitingOutput(Framed<Stream, DogCodec>),
-    /// Waiting to send a transaction to the remote.
-    PendingSend(Framed<Stream, DogCodec>, proto::RPC),
+    /// Waiting to send a transaction to the remote. The transaction id is set if the transaction
+    /// is published by the local node.
+    PendingSend(Framed<Stream, DogCodec>, proto::RPC, Option<TransactionId>),
     /// Waiting to flush the outbound substream so that the data arrives to the remote.
-    PendingFlush(Framed<Stream, DogCodec>),
+    PendingFlush(Framed<Stream, DogCodec>, Option<TransactionId>),
     /// An error occurred during processing.
     Poisoned,
 }
@@ -120,6 +134,9 @@ impl Handler {
             outbound_substream_establishing: false,
             outbound_substream_attempts: 0,
             inbound_substream_attempts: 0,
+            sent_transactions: VecDeque::new(),
+            protocol_supported: false,
+            protocol_supported_sent: false,
         })
     }
 }
@@ -127,6 +144,7 @@ impl Handler {
 impl EnabledHandler {
     fn on_fully_negotiated_inbound(&mut self, substream: Framed<Stream, DogCodec>) {
         trace!("New inbound substream request");
+        self.protocol_supported = true;
         self.inbound_substream = Some(InboundSubstreamState::WaitingInput(substream));
     }
 
@@ -142,6 +160,7 @@ impl EnabledHandler {
             self.outbound_substream.is_none(),
             "Established an outbound substream with one already available"
         );
+        self.protocol_supported = true;
         self.outbound_substream = Some(OutboundSubstreamState::WaitingOutput(substream));
     }
 
@@ -155,7 +174,16 @@ impl EnabledHandler {
             <Handler as ConnectionHandler>::ToBehaviour,
         >,
     > {
-        if !self.send_queue.poll_is_empty(cx)
+        if !self.protocol_supported_sent && self.protocol_supported {
+            self.protocol_supported_sent = true;
+            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(
+                HandlerEvent::ProtocolSupported,
+            ));
+        }
+
+        // The first outbound substream is requested as soon as the connection is established, to
+        // learn whether the remote peer supports the protocol.
+        if (self.outbound_substream_attempts == 0 || !self.send_queue.poll_is_empty(cx))
             && self.outbound_substream.is_none()
             && !self.outbound_substream_establishing
         {
@@ -175,12 +203,10 @@ impl EnabledHandler {
                     {
                         match transaction {
                             RpcOut::Publish {
-                                tx: _,
-                                ref mut timeout,
+                                ref mut timeout, ..
                             }
                             | RpcOut::Forward {
-                                tx: _,
-                                ref mut timeout,
+                                ref mut timeout, ..
                             } => {
                                 if Pin::new(timeout).poll(cx).is_ready() {
                                     self.outbound_substream =
@@ -192,9 +218,14 @@ impl EnabledHandler {
                             }
                             _ => {} // All other transactions are not time-bound.
                         }
+                        let published_tx_id = match transaction {
+                            RpcOut::Publish { ref tx_id, .. } => Some(tx_id.clone()),
+                            _ => None,
+                        };
                         self.outbound_substream = Some(OutboundSubstreamState::PendingSend(
                             substream,
                             transaction.into_protobuf(),
+                            published_tx_id,
                         ));
                         continue;
                     }
@@ -203,13 +234,16 @@ impl EnabledHandler {
                         Some(OutboundSubstreamState::WaitingOutput(substream));
                     break;
                 }
-                Some(OutboundSubstreamState::PendingSend(mut substream, rpc)) => {
+                Some(OutboundSubstreamState::PendingSend(mut substream, rpc, published_tx_id)) => {
                     match Sink::poll_ready(Pin::new(&mut substream), cx) {
                         Poll::Ready(Ok(())) => {
                             match Sink::start_send(Pin::new(&mut substream), rpc) {
                                 Ok(()) => {
                                     self.outbound_substream =
-                                        Some(OutboundSubstreamState::PendingFlush(substream))
+                                        Some(OutboundSubstreamState::PendingFlush(
+                                            substream,
+                                            published_tx_id,
+                                        ))
                                 }
                                 Err(e) => {
                                     debug!("Failed to send transaction on outbound stream: {e}");
@@ -224,17 +258,23 @@ impl EnabledHandler {
                             break;
                         }
                         Poll::Pending => {
-                            self.outbound_substream =
-                                Some(OutboundSubstreamState::PendingSend(substream, rpc));
+                            self.outbound_substream = Some(OutboundSubstreamState::PendingSend(
+                                substream,
+                                rpc,
+                                published_tx_id,
+                            ));
                             break;
                         }
                     }
                 }
-                Some(OutboundSubstreamState::PendingFlush(mut substream)) => {
+                Some(OutboundSubstreamState::PendingFlush(mut substream, published_tx_id)) => {
                     match Sink::poll_flush(Pin::new(&mut substream), cx) {
                         Poll::Ready(Ok(())) => {
                             self.outbound_substream =
-                                Some(OutboundSubstreamState::WaitingOutput(substream))
+                                Some(OutboundSubstreamState::WaitingOutput(substream));
+                            if let Some(tx_id) = published_tx_id {
+                                self.sent_transactions.push_back(tx_id);
+                            }
                         }
                         Poll::Ready(Err(e)) => {
                             debug!("Failed to flush outbound stream: {e}");
@@ -242,8 +282,10 @@ impl EnabledHandler {
                             break;
                         }
                         Poll::Pending => {
-                            self.outbound_substream =
-                                Some(OutboundSubstreamState::PendingFlush(substream));
+                            self.outbound_substream = Some(OutboundSubstreamState::PendingFlush(
+                                substream,
+                                published_tx_id,
+                            ));
                             break;
                         }
                     }
@@ -315,6 +357,12 @@ impl EnabledHandler {
             }
         }
 
+        if let Some(tx_id) = self.sent_transactions.pop_front() {
+            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(
+                HandlerEvent::TransactionSent(tx_id),
+            ));
+        }
+
         if let Poll::Ready(Some(rpc)) = self.send_queue.poll_stale(cx) {
             return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(
                 HandlerEvent::TransactionDropped(rpc),
@@ -0,0 +1,229 @@
+use std::hash::Hasher;
+
+use fnv::FnvHasher;
+
+use crate::types::TransactionId;
+
+/// The number of cells each key is inserted into.
+pub(crate) const HASH_COUNT: usize = 3;
+/// The seeds used to derive the cell of a key in each sub-table.
+const SEEDS: [u64; HASH_COUNT] = [
+    0x5bd1_e995_0000_0001,
+    0x9e37_79b9_7f4a_7c15,
+    0xc2b2_ae3d_27d4_eb4f,
+];
+/// The size of an encoded cell: a 4-byte count followed by two 8-byte sums.
+const CELL_SIZE: usize = 20;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Cell {
+    count: i32,
+    key_sum: u64,
+    hash_sum: u64,
+}
+
+impl Cell {
+    fn is_empty(&self) -> bool {
+        self.count == 0 && self.key_sum == 0 && self.hash_sum == 0
+    }
+
+    fn is_pure(&self) -> bool {
+        (self.count == 1 || self.count == -1) && self.hash_sum == check_hash(self.key_sum)
+    }
+}
+
+/// The SplitMix64 finalizer, used to spread keys over cells and to check pure cells.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+fn check_hash(key: u64) -> u64 {
+    mix(key ^ 0xa076_1d64_78bd_642f)
+}
+
+/// Returns the 64-bit short id of a transaction inserted in sketches. It only depends on the
+/// transaction id, so that it is the same on every node.
+pub(crate) fn short_id(tx_id: &TransactionId) -> u64 {
+    let mut hasher = FnvHasher::default();
+    hasher.write(&tx_id.0);
+    hasher.finish()
+}
+
+/// Returns the number of cells of a sketch of at least `size` cells.
+fn cell_count(size: usize) -> usize {
+    size.max(1).div_ceil(HASH_COUNT) * HASH_COUNT
+}
+
+/// Returns the length of the encoding of a sketch created with [`Iblt::new`].
+pub(crate) fn encoded_len(size: usize) -> usize {
+    cell_count(size) * CELL_SIZE
+}
+
+/// An invertible Bloom lookup table of transaction short ids.
+///
+/// Subtracting the sketch of a peer from ours and decoding the result yields the short ids that
+/// are in only one of the two sets, as long as the difference is small compared to the number of
+/// cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Iblt {
+    cells: Vec<Cell>,
+}
+
+impl Iblt {
+    /// Creates an empty sketch of at least `size` cells, rounded up to a multiple of the number of
+    /// hash functions.
+    pub(crate) fn new(size: usize) -> Self {
+        Iblt {
+            cells: vec![Cell::default(); cell_count(size)],
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn indices(&self, key: u64) -> [usize; HASH_COUNT] {
+        let sub_table_len = self.cells.len() / HASH_COUNT;
+        std::array::from_fn(|i| {
+            i * sub_table_len + (mix(key ^ SEEDS[i]) % sub_table_len as u64) as usize
+        })
+    }
+
+    fn update(&mut self, key: u64, delta: i32) {
+        let hash = check_hash(key);
+        for index in self.indices(key) {
+            let cell = &mut self.cells[index];
+            cell.count = cell.count.wrapping_add(delta);
+            cell.key_sum ^= key;
+            cell.hash_sum ^= hash;
+        }
+    }
+
+    pub(crate) fn insert(&mut self, key: u64) {
+        self.update(key, 1);
+    }
+
+    /// Subtracts `other` from this sketch. Both sketches must have the same number of cells.
+    pub(crate) fn subtract(&mut self, other: &Iblt) {
+        debug_assert_eq!(self.cells.len(), other.cells.len());
+        for (cell, other) in self.cells.iter_mut().zip(&other.cells) {
+            cell.count = cell.count.wrapping_sub(other.count);
+            cell.key_sum ^= other.key_sum;
+            cell.hash_sum ^= other.hash_sum;
+        }
+    }
+
+    /// Decodes a subtracted sketch into the keys only present in the first set and the keys only
+    /// present in the second one. Returns `None` if the difference is too large to be decoded.
+    pub(crate) fn decode(mut self) -> Option<(Vec<u64>, Vec<u64>)> {
+        let mut local = Vec::new();
+        let mut remote = Vec::new();
+
+        // Each peeled key clears at least one cell, a well-formed sketch is decoded in fewer steps.
+        for _ in 0..self.cells.len() * HASH_COUNT {
+            let Some(index) = self.cells.iter().position(Cell::is_pure) else {
+                break;
+            };
+            let cell = self.cells[index];
+            // A crafted sketch may contain a pure cell whose key does not map to it, peeling it
+            // would not clear the cell.
+            if !self.indices(cell.key_sum).contains(&index) {
+                return None;
+            }
+            if cell.count == 1 {
+                local.push(cell.key_sum);
+            } else {
+                remote.push(cell.key_sum);
+            }
+            self.update(cell.key_sum, -cell.count);
+        }
+
+        self.cells
+            .iter()
+            .all(Cell::is_empty)
+            .then_some((local, remote))
+    }
+
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.cells.len() * CELL_SIZE);
+        for cell in &self.cells {
+            bytes.extend_from_slice(&cell.count.to_le_bytes());
+            bytes.extend_from_slice(&cell.key_sum.to_le_bytes());
+            bytes.extend_from_slice(&cell.hash_sum.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decodes a sketch received from a peer. Returns `None` if the bytes are not a valid sketch.
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.is_empty() || bytes.len() % (CELL_SIZE * HASH_COUNT) != 0 {
+            return None;
+        }
+
+        let cells = bytes
+            .chunks_exact(CELL_SIZE)
+            .map(|chunk| Cell {
+                count: i32::from_le_bytes(chunk[0..4].try_into().expect("4 bytes")),
+                key_sum: u64::from_le_bytes(chunk[4..12].try_into().expect("8 bytes")),
+                hash_sum: u64::from_le_bytes(chunk[12..20].try_into().expect("8 bytes")),
+            })
+            .collect();
+
+        Some(Iblt { cells })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sketch(keys: impl IntoIterator<Item = u64>, size: usize) -> Iblt {
+        let mut iblt = Iblt::new(size);
+        for key in keys {
+            iblt.insert(key);
+        }
+        iblt
+    }
+
+    #[test]
+    fn decode_difference() {
+        let mut local = sketch((0..100).chain(1000..1005), 60);
+        let remote = sketch((0..100).chain(2000..2003), 60);
+        local.subtract(&remote);
+
+        let (mut only_local, mut only_remote) = local.decode().expect("decodable difference");
+        only_local.sort();
+        only_remote.sort();
+
+        assert_eq!(only_local, (1000..1005).collect::<Vec<_>>());
+        assert_eq!(only_remote, (2000..2003).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn decode_overloaded() {
+        let mut local = sketch(0..100, 12);
+        let remote = sketch(100..200, 12);
+        local.subtract(&remote);
+
+        assert!(local.decode().is_none());
+    }
+
+    #[test]
+    fn decode_adversarial() {
+        let mut iblt = Iblt::new(6);
+        // A pure cell holding a key that does not map to it.
+        let key = (0..)
+            .find(|&key| !iblt.indices(key).contains(&0))
+            .expect("a key not mapping to the first cell");
+        iblt.cells[0] = Cell {
+            count: 1,
+            key_sum: key,
+            hash_sum: check_hash(key),
+        };
+
+        let received = Iblt::from_bytes(&iblt.to_bytes()).expect("valid encoding");
+        assert!(received.decode().is_none());
+    }
+}
@@ -0,0 +1,143 @@
+use std::{collections::HashMap, time::Duration};
+
+use libp2p::PeerId;
+use web_time::Instant;
+
+use crate::{time_cache::DuplicateCache, types::TransactionId};
+
+/// The transactions recently exchanged with a single peer.
+struct PeerTransactions {
+    /// Transactions sent to the peer.
+    sent: DuplicateCache<TransactionId, ()>,
+    /// Transactions received from the peer.
+    received: DuplicateCache<TransactionId, ()>,
+    /// The last time a transaction sent to the peer was evicted before it expired.
+    last_sent_eviction: Option<Instant>,
+}
+
+/// Bounded per-peer sets of the transactions recently sent to and received from each peer, used
+/// to avoid forwarding a transaction to a peer known to have it and to validate `HaveTx` messages.
+pub(crate) struct KnownTransactions {
+    peers: HashMap<PeerId, PeerTransactions>,
+    /// The time a transaction is remembered.
+    ttl: Duration,
+    /// The maximum number of sent and of received transactions remembered per peer, if any.
+    capacity: Option<usize>,
+}
+
+impl KnownTransactions {
+    pub(crate) fn new(ttl: Duration, capacity: Option<usize>) -> Self {
+        KnownTransactions {
+            peers: HashMap::new(),
+            ttl,
+            capacity,
+        }
+    }
+
+    fn peer_mut(&mut self, peer: PeerId) -> &mut PeerTransactions {
+        let (ttl, capacity) = (self.ttl, self.capacity);
+        self.peers.entry(peer).or_insert_with(|| PeerTransactions {
+            sent: DuplicateCache::new(ttl, capacity),
+            received: DuplicateCache::new(ttl, capacity),
+            last_sent_eviction: None,
+        })
+    }
+
+    pub(crate) fn register_sent(&mut self, peer: PeerId, tx_id: TransactionId) {
+        let known = self.peer_mut(peer);
+        known.sent.insert(tx_id, ());
+        if known.sent.take_removed().1 > 0 {
+            known.last_sent_eviction = Some(Instant::now());
+        }
+    }
+
+    pub(crate) fn register_received(&mut self, peer: PeerId, tx_id: TransactionId) {
+        self.peer_mut(peer).received.insert(tx_id, ());
+    }
+
+    /// Returns whether the transaction was recently sent to the peer, or `None` if it may have
+    /// been evicted, i.e. if a transaction sent to the peer was evicted less than the ttl ago.
+    pub(crate) fn was_sent(&self, peer: &PeerId, tx_id: &TransactionId) -> Option<bool> {
+        let Some(known) = self.peers.get(peer) else {
+            return Some(false);
+        };
+        if known.sent.contains(tx_id) {
+            return Some(true);
+        }
+        if known
+            .last_sent_eviction
+            .is_some_and(|eviction| eviction.elapsed() < self.ttl)
+        {
+            return None;
+        }
+        Some(false)
+    }
+
+    /// Returns whether the peer is known to have the transaction, because we sent it to the peer
+    /// or received it from the peer.
+    pub(crate) fn has_transaction(&self, peer: &PeerId, tx_id: &TransactionId) -> bool {
+        self.peers
+            .get(peer)
+            .is_some_and(|known| known.sent.contains(tx_id) || known.received.contains(tx_id))
+    }
+
+    pub(crate) fn remove_peer(&mut self, peer: &PeerId) {
+        self.peers.remove(peer);
+    }
+
+    /// Removes the expired transactions.
+    pub(crate) fn remove_expired(&mut self) {
+        for known in self.peers.values_mut() {
+            known.sent.remove_expired();
+            known.received.remove_expired();
+            // The sets are not reported in the metrics.
+            known.sent.take_removed();
+            known.received.take_removed();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicted_sent_transactions() {
+        let mut known = KnownTransactions::new(Duration::from_secs(60), Some(2));
+        let peer = PeerId::random();
+        let tx_ids = (0..3u8)
+            .map(|i| TransactionId::new(&[i]))
+            .collect::<Vec<_>>();
+
+        known.register_sent(peer, tx_ids[0].clone());
+        known.register_sent(peer, tx_ids[1].clone());
+        assert_eq!(known.was_sent(&peer, &tx_ids[0]), Some(true));
+        assert_eq!(known.was_sent(&peer, &tx_ids[2]), Some(false));
+
+        // Once a transaction has been evicted, a missing one may have been sent.
+        known.register_sent(peer, tx_ids[2].clone());
+        assert_eq!(known.was_sent(&peer, &tx_ids[0]), None);
+        assert_eq!(known.was_sent(&peer, &tx_ids[2]), Some(true));
+
+        assert_eq!(known.was_sent(&PeerId::random(), &tx_ids[0]), Some(false));
+    }
+
+    #[test]
+    fn unbounded_sent_transactions() {
+        let mut known = KnownTransactions::new(Duration::from_secs(60), None);
+        let peer = PeerId::random();
+
+        for i in 0..10_000u32 {
+            known.register_sent(peer, TransactionId::new(&i.to_be_bytes()));
+        }
+
+        assert_eq!(
+            known.was_sent(&peer, &TransactionId::new(&0u32.to_be_bytes())),
+            Some(true)
+        );
+        assert_eq!(
+            known.was_sent(&peer, &TransactionId::new(b"unknown")),
+            Some(false)
+        );
+    }
+}
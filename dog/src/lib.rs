@@ -1,21 +1,45 @@
 mod behaviour;
+#[cfg(feature = "gossipsub")]
+mod bridge;
 mod config;
 mod dog;
+mod duplicate_filter;
 mod error;
 mod handler;
+mod iblt;
+mod known_transactions;
 mod metrics;
+mod outcome;
 pub mod protocol;
+mod published;
+mod publisher;
+mod rate_limit;
 mod rpc;
 mod rpc_proto;
+mod seqno_window;
+mod signer;
+mod store;
+mod sync;
 mod time_cache;
 mod transform;
 mod types;
 
 pub use self::{
     behaviour::{Behaviour, Event, TransactionAuthenticity},
-    config::{Config, ConfigBuilder, ValidationMode},
-    dog::Route,
+    config::{Config, ConfigBuilder, DuplicateFilterMode, TransactionIdMode, ValidationMode},
+    dog::{Misbehaviour, Route},
     error::{PublishError, ValidationError},
-    transform::{DataTransform, IdentityTransform},
-    types::{RawTransaction, Transaction, TransactionId},
+    publisher::DogPublisher,
+    signer::{KeypairSigner, TransactionSigner},
+    transform::{DataTransform, IdentityTransform, InboundContext, OutboundContext},
+    types::{PublishOptions, RawTransaction, Transaction, TransactionId},
 };
+
+#[cfg(feature = "gossipsub")]
+pub use self::bridge::GossipsubBridge;
+#[cfg(feature = "encryption")]
+pub use self::transform::EncryptTransform;
+#[cfg(feature = "snappy")]
+pub use self::transform::SnappyTransform;
+#[cfg(feature = "zstd")]
+pub use self::transform::ZstdTransform;
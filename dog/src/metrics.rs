@@ -37,11 +37,34 @@ pub(crate) struct Metrics {
     txs_recv_counts: Counter,
     /// Number of invalid transactions received.
     txs_invalid_counts: Counter,
+    /// Number of replayed transactions received.
+    txs_replay_counts: Counter,
     /// Number of bytes received.
     txs_recv_bytes: Counter,
+    /// Number of transactions dropped because the peer exceeded its rate limit.
+    txs_rate_limited_counts: Counter,
+    /// Number of control messages dropped because the peer exceeded its rate limit.
+    control_rate_limited_counts: Counter,
+    /// Number of reset route messages received and ignored.
+    reset_route_rejected_counts: Counter,
+    /// Number of transaction ids received in have tx messages and ignored.
+    have_tx_rejected_counts: Counter,
+    /// Number of routes not disabled to keep the minimum number of active routes to a peer.
+    disable_route_rejected_counts: Counter,
+    /// Number of routes not disabled because the maximum number of disabled routes to a peer was
+    /// reached.
+    disabled_routes_cap_reached_counts: Counter,
+    /// Number of transactions not forwarded because their time-to-live expired.
+    ttl_expired_counts: Counter,
 
     /// Transactions cache size.
     txs_cache_size: Gauge,
+    /// Estimated memory used by the transactions cache, in bytes.
+    txs_cache_memory_bytes: Gauge,
+    /// Number of transaction ids expired from the cache.
+    txs_cache_expired_counts: Counter,
+    /// Number of transaction ids evicted from the cache because it was full.
+    txs_cache_evicted_counts: Counter,
 }
 
 impl Metrics {
@@ -60,8 +83,19 @@ impl Metrics {
         let txs_recv_counts_unfiltered = Counter::default();
         let txs_recv_counts = Counter::default();
         let txs_invalid_counts = Counter::default();
+        let txs_replay_counts = Counter::default();
         let txs_recv_bytes = Counter::default();
+        let txs_rate_limited_counts = Counter::default();
+        let control_rate_limited_counts = Counter::default();
+        let reset_route_rejected_counts = Counter::default();
+        let have_tx_rejected_counts = Counter::default();
+        let disable_route_rejected_counts = Counter::default();
+        let disabled_routes_cap_reached_counts = Counter::default();
+        let ttl_expired_counts = Counter::default();
         let txs_cache_size = Gauge::default();
+        let txs_cache_memory_bytes = Gauge::default();
+        let txs_cache_expired_counts = Counter::default();
+        let txs_cache_evicted_counts = Counter::default();
 
         registry.register("peers_count", "Number of peers.", peers_count.clone());
         registry.register("redundancy", "Redundancy.", redundancy.clone());
@@ -125,16 +159,71 @@ impl Metrics {
             "Number of invalid transactions received.",
             txs_invalid_counts.clone(),
         );
+        registry.register(
+            "txs_replay_counts",
+            "Number of replayed transactions received.",
+            txs_replay_counts.clone(),
+        );
         registry.register(
             "txs_recv_bytes",
             "Number of bytes received.",
             txs_recv_bytes.clone(),
         );
+        registry.register(
+            "txs_rate_limited_counts",
+            "Number of transactions dropped because the peer exceeded its rate limit.",
+            txs_rate_limited_counts.clone(),
+        );
+        registry.register(
+            "control_rate_limited_counts",
+            "Number of control messages dropped because the peer exceeded its rate limit.",
+            control_rate_limited_counts.clone(),
+        );
+        registry.register(
+            "reset_route_rejected_counts",
+            "Number of reset route messages received and ignored.",
+            reset_route_rejected_counts.clone(),
+        );
+        registry.register(
+            "have_tx_rejected_counts",
+            "Number of transaction ids received in have tx messages and ignored.",
+            have_tx_rejected_counts.clone(),
+        );
+        registry.register(
+            "disable_route_rejected_counts",
+            "Number of routes not disabled to keep the minimum number of active routes to a peer.",
+            disable_route_rejected_counts.clone(),
+        );
+        registry.register(
+            "disabled_routes_cap_reached_counts",
+            "Number of routes not disabled because the maximum number of disabled routes to a peer was reached.",
+            disabled_routes_cap_reached_counts.clone(),
+        );
+        registry.register(
+            "ttl_expired_counts",
+            "Number of transactions not forwarded because their time-to-live expired.",
+            ttl_expired_counts.clone(),
+        );
         registry.register(
             "txs_cache_size",
             "Transactions cache size.",
             txs_cache_size.clone(),
         );
+        registry.register(
+            "txs_cache_memory_bytes",
+            "Estimated memory used by the transactions cache, in bytes.",
+            txs_cache_memory_bytes.clone(),
+        );
+        registry.register(
+            "txs_cache_expired_counts",
+            "Number of transaction ids expired from the cache.",
+            txs_cache_expired_counts.clone(),
+        );
+        registry.register(
+            "txs_cache_evicted_counts",
+            "Number of transaction ids evicted from the cache because it was full.",
+            txs_cache_evicted_counts.clone(),
+        );
 
         Self {
             peers_count,
@@ -151,8 +240,19 @@ impl Metrics {
             txs_recv_counts_unfiltered,
             txs_recv_counts,
             txs_invalid_counts,
+            txs_replay_counts,
             txs_recv_bytes,
+            txs_rate_limited_counts,
+            control_rate_limited_counts,
+            reset_route_rejected_counts,
+            have_tx_rejected_counts,
+            disable_route_rejected_counts,
+            disabled_routes_cap_reached_counts,
+            ttl_expired_counts,
             txs_cache_size,
+            txs_cache_memory_bytes,
+            txs_cache_expired_counts,
+            txs_cache_evicted_counts,
         }
     }
 
@@ -218,11 +318,51 @@ impl Metrics {
         self.txs_invalid_counts.inc();
     }
 
-    pub(crate) fn set_txs_cache_size(&mut self, size: usize) {
+    pub(crate) fn register_replay_tx(&mut self) {
+        self.txs_replay_counts.inc();
+    }
+
+    pub(crate) fn register_rate_limited(&mut self, transactions: usize, control_messages: usize) {
+        self.txs_rate_limited_counts.inc_by(transactions as u64);
+        self.control_rate_limited_counts
+            .inc_by(control_messages as u64);
+    }
+
+    pub(crate) fn register_reset_route_rejected(&mut self) {
+        self.reset_route_rejected_counts.inc();
+    }
+
+    pub(crate) fn register_have_tx_rejected(&mut self, count: usize) {
+        self.have_tx_rejected_counts.inc_by(count as u64);
+    }
+
+    pub(crate) fn register_disable_route_rejected(&mut self) {
+        self.disable_route_rejected_counts.inc();
+    }
+
+    pub(crate) fn register_disabled_routes_cap_reached(&mut self) {
+        self.disabled_routes_cap_reached_counts.inc();
+    }
+
+    pub(crate) fn register_ttl_expired(&mut self) {
+        self.ttl_expired_counts.inc();
+    }
+
+    pub(crate) fn set_txs_cache_size(&mut self, size: usize, memory: usize) {
         if let Ok(size) = size.try_into() {
             self.txs_cache_size.set(size);
         } else {
             tracing::error!("Failed to set transactions cache size");
         }
+        if let Ok(memory) = memory.try_into() {
+            self.txs_cache_memory_bytes.set(memory);
+        } else {
+            tracing::error!("Failed to set transactions cache memory");
+        }
+    }
+
+    pub(crate) fn register_txs_cache_removed(&mut self, expired: usize, evicted: usize) {
+        self.txs_cache_expired_counts.inc_by(expired as u64);
+        self.txs_cache_evicted_counts.inc_by(evicted as u64);
     }
 }
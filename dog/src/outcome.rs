@@ -0,0 +1,200 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    time::Duration,
+};
+
+use fnv::FnvHashMap;
+use libp2p::PeerId;
+use web_time::Instant;
+
+use crate::types::TransactionId;
+
+/// The aggregated per-peer send outcome of a published transaction.
+#[derive(Debug)]
+pub(crate) struct PublishOutcome {
+    pub(crate) transaction_id: TransactionId,
+    pub(crate) sent_to: Vec<PeerId>,
+    pub(crate) dropped_at: Vec<PeerId>,
+}
+
+struct Tracker {
+    sent_to: Vec<PeerId>,
+    dropped_at: Vec<PeerId>,
+    /// Peers the transaction has been queued to and that have not reported yet.
+    pending: HashSet<PeerId>,
+    expiration: Instant,
+}
+
+/// Tracks the peers our published transactions have been queued to until each of them has
+/// either sent or dropped the transaction.
+pub(crate) struct PublishOutcomes {
+    trackers: FnvHashMap<TransactionId, Tracker>,
+    /// List of tracked transactions in order of expiration.
+    list: VecDeque<(TransactionId, Instant)>,
+    /// The time after which peers that have not reported are considered to have dropped the
+    /// transaction.
+    ttl: Duration,
+}
+
+impl PublishOutcomes {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        PublishOutcomes {
+            trackers: FnvHashMap::default(),
+            list: VecDeque::new(),
+            ttl,
+        }
+    }
+
+    /// Starts tracking a published transaction queued to the `queued_to` peers. `dropped_at`
+    /// holds the peers the transaction could not be queued to. If the transaction is already
+    /// tracked, e.g. when it is republished, the peers are added to its pending outcome.
+    pub(crate) fn track(
+        &mut self,
+        transaction_id: TransactionId,
+        queued_to: Vec<PeerId>,
+        dropped_at: Vec<PeerId>,
+    ) {
+        let expiration = Instant::now() + self.ttl;
+        self.list.push_back((transaction_id.clone(), expiration));
+
+        let tracker = self
+            .trackers
+            .entry(transaction_id)
+            .or_insert_with(|| Tracker {
+                sent_to: Vec::new(),
+                dropped_at: Vec::new(),
+                pending: HashSet::new(),
+                expiration,
+            });
+        tracker.expiration = expiration;
+
+        // A peer that dropped the transaction before is pending again once it is queued again.
+        tracker.dropped_at.retain(|peer| !queued_to.contains(peer));
+        for peer in dropped_at {
+            if !tracker.pending.contains(&peer)
+                && !tracker.sent_to.contains(&peer)
+                && !tracker.dropped_at.contains(&peer)
+            {
+                tracker.dropped_at.push(peer);
+            }
+        }
+        tracker.pending.extend(queued_to);
+    }
+
+    fn report(
+        &mut self,
+        transaction_id: &TransactionId,
+        peer: PeerId,
+        sent: bool,
+    ) -> Option<PublishOutcome> {
+        let tracker = self.trackers.get_mut(transaction_id)?;
+        if !tracker.pending.remove(&peer) {
+            return None;
+        }
+        if sent {
+            tracker.sent_to.push(peer);
+        } else {
+            tracker.dropped_at.push(peer);
+        }
+        if !tracker.pending.is_empty() {
+            return None;
+        }
+
+        self.trackers
+            .remove(transaction_id)
+            .map(|tracker| PublishOutcome {
+                transaction_id: transaction_id.clone(),
+                sent_to: tracker.sent_to,
+                dropped_at: tracker.dropped_at,
+            })
+    }
+
+    /// Records that `peer` has written the transaction to its outbound substream.
+    pub(crate) fn sent(
+        &mut self,
+        transaction_id: &TransactionId,
+        peer: PeerId,
+    ) -> Option<PublishOutcome> {
+        self.report(transaction_id, peer, true)
+    }
+
+    /// Records that `peer` has dropped the transaction.
+    pub(crate) fn dropped(
+        &mut self,
+        transaction_id: &TransactionId,
+        peer: PeerId,
+    ) -> Option<PublishOutcome> {
+        self.report(transaction_id, peer, false)
+    }
+
+    /// Records that `peer` has disconnected, dropping every transaction still queued to it.
+    pub(crate) fn peer_disconnected(&mut self, peer: PeerId) -> Vec<PublishOutcome> {
+        let transaction_ids = self
+            .trackers
+            .iter()
+            .filter(|(_, tracker)| tracker.pending.contains(&peer))
+            .map(|(transaction_id, _)| transaction_id.clone())
+            .collect::<Vec<_>>();
+
+        transaction_ids
+            .into_iter()
+            .filter_map(|transaction_id| self.dropped(&transaction_id, peer))
+            .collect()
+    }
+
+    /// Returns the outcome of the transactions tracked for longer than the ttl, considering the
+    /// peers that have not reported as having dropped them.
+    pub(crate) fn remove_expired(&mut self) -> Vec<PublishOutcome> {
+        let now = Instant::now();
+        let mut outcomes = Vec::new();
+
+        while let Some((transaction_id, expiration)) = self.list.pop_front() {
+            if expiration > now {
+                self.list.push_front((transaction_id, expiration));
+                break;
+            }
+            // The transaction may have completed and been tracked again since.
+            if self
+                .trackers
+                .get(&transaction_id)
+                .is_none_or(|tracker| tracker.expiration > now)
+            {
+                continue;
+            }
+            if let Some(mut tracker) = self.trackers.remove(&transaction_id) {
+                tracker.dropped_at.extend(tracker.pending.drain());
+                outcomes.push(PublishOutcome {
+                    transaction_id,
+                    sent_to: tracker.sent_to,
+                    dropped_at: tracker.dropped_at,
+                });
+            }
+        }
+
+        outcomes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn track_republished_transaction() {
+        let mut outcomes = PublishOutcomes::new(Duration::from_secs(60));
+        let transaction_id = TransactionId::new(b"tx");
+        let (peer_a, peer_b) = (PeerId::random(), PeerId::random());
+
+        outcomes.track(transaction_id.clone(), vec![peer_a], vec![peer_b]);
+        // Republished while the first send to `peer_a` is still pending.
+        outcomes.track(transaction_id.clone(), vec![peer_b], vec![]);
+
+        assert!(outcomes.sent(&transaction_id, peer_a).is_none());
+        let outcome = outcomes
+            .sent(&transaction_id, peer_b)
+            .expect("All peers to have reported");
+
+        assert_eq!(outcome.sent_to, vec![peer_a, peer_b]);
+        assert!(outcome.dropped_at.is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use std::{convert::Infallible, future::Future, iter, pin::Pin};
+use std::{convert::Infallible, future::Future, iter, num::NonZeroU32, pin::Pin};
 
 use asynchronous_codec::{Decoder, Encoder, Framed};
 use futures::future;
@@ -130,9 +130,11 @@ impl DogCodec {
         }
 
         let mut transaction_sig = transaction.clone();
-        // Signature and key fields are not part of the signature
+        // Signature, key, hops and ttl fields are not part of the signature
         transaction_sig.signature = vec![];
         transaction_sig.key = vec![];
+        transaction_sig.hops = 0;
+        transaction_sig.ttl = 0;
         let mut buf = Vec::with_capacity(transaction_sig.get_size());
         let mut writer = Writer::new(&mut buf);
         transaction_sig
@@ -142,6 +144,98 @@ impl DogCodec {
         signature_bytes.extend_from_slice(&buf);
         public_key.verify(&signature_bytes, &transaction.signature)
     }
+
+    /// Validates the headers, author, sequence number and signature fields of a received transaction
+    /// according to the configured [`ValidationMode`].
+    fn validate_transaction(
+        &self,
+        transaction: &proto::Transaction,
+    ) -> Result<(), ValidationError> {
+        let mut verify_signature = false;
+        let mut verify_sequence_no = false;
+        let mut verify_source = false;
+
+        match self.validation_mode {
+            ValidationMode::Strict => {
+                // Validate everything
+                verify_signature = true;
+                verify_sequence_no = true;
+                verify_source = true;
+            }
+            ValidationMode::Permissive => {
+                // If the fields exist, validate them
+                if !transaction.signature.is_empty() {
+                    verify_signature = true;
+                }
+                if transaction.seqno != 0 {
+                    verify_sequence_no = true;
+                }
+                if !transaction.from.is_empty() {
+                    verify_source = true;
+                }
+            }
+            ValidationMode::Anonymous => {
+                if !transaction.signature.is_empty() {
+                    tracing::warn!(
+                        "Signature field was non-empty and anonymous validation mode is set"
+                    );
+                    return Err(ValidationError::SignaturePresent);
+                }
+                if transaction.seqno != 0 {
+                    tracing::warn!(
+                        "Sequence number was non-empty and anonymous validation mode is set"
+                    );
+                    return Err(ValidationError::SequenceNumberPresent);
+                }
+                if !transaction.from.is_empty() {
+                    tracing::warn!("Author was non-empty and anonymous validation mode is set");
+                    return Err(ValidationError::AuthorPresent);
+                }
+            }
+            ValidationMode::Author => {
+                verify_sequence_no = true;
+                verify_source = true;
+            }
+            ValidationMode::None => {
+                // Always verify the author of the transaction
+                verify_source = true;
+            }
+        }
+
+        // Headers are re-encoded from a map when the transaction is forwarded, so only the
+        // canonical encoding can keep a valid signature.
+        if transaction
+            .headers
+            .windows(2)
+            .any(|pair| pair[0].key >= pair[1].key)
+        {
+            tracing::debug!("Transaction headers are not sorted by key");
+            return Err(ValidationError::InvalidHeaders);
+        }
+
+        if verify_source && PeerId::from_bytes(&transaction.from).is_err() {
+            tracing::debug!("Transaction author has an invalid PeerId");
+            return Err(ValidationError::InvalidPeerId);
+        }
+
+        if verify_sequence_no && transaction.seqno == 0 {
+            tracing::debug!("Sequence number not present but expected");
+            return Err(ValidationError::EmptySequenceNumber);
+        }
+
+        if verify_signature {
+            if transaction.signature.is_empty() {
+                tracing::debug!("Signature not present but expected");
+                return Err(ValidationError::MissingSignature);
+            }
+            if !DogCodec::verify_signature(transaction) {
+                tracing::warn!("Invalid signature for the received transaction");
+                return Err(ValidationError::InvalidSignature);
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Encoder for DogCodec {
@@ -170,68 +264,15 @@ impl Decoder for DogCodec {
         let mut invalid_transactions = Vec::new();
 
         for transaction in rpc.txs.into_iter() {
-            let mut verify_signature = false;
+            let validation = self.validate_transaction(&transaction);
 
-            match self.validation_mode {
-                ValidationMode::Strict => {
-                    verify_signature = true;
-                }
-                ValidationMode::None => {}
-            }
-
-            // Always verify the author of the transaction
-            let source = match PeerId::from_bytes(&transaction.from) {
-                Ok(peer_id) => peer_id,
-                Err(_) => {
-                    invalid_transactions.push((
-                        RawTransaction {
-                            from: PeerId::random(),
-                            seqno: transaction.seqno,
-                            data: transaction.data,
-                            signature: if transaction.signature.is_empty() {
-                                None
-                            } else {
-                                Some(transaction.signature)
-                            },
-                            key: if transaction.key.is_empty() {
-                                None
-                            } else {
-                                Some(transaction.key)
-                            },
-                        },
-                        ValidationError::InvalidPeerId,
-                    ));
-                    continue;
-                }
-            };
-
-            if verify_signature && !DogCodec::verify_signature(&transaction) {
-                tracing::warn!("Invalid signature for the received transaction");
-
-                invalid_transactions.push((
-                    RawTransaction {
-                        from: source,
-                        seqno: transaction.seqno,
-                        data: transaction.data,
-                        signature: if transaction.signature.is_empty() {
-                            None
-                        } else {
-                            Some(transaction.signature)
-                        },
-                        key: if transaction.key.is_empty() {
-                            None
-                        } else {
-                            Some(transaction.key)
-                        },
-                    },
-                    ValidationError::InvalidSignature,
-                ));
-                continue;
-            }
-
-            transactions.push(RawTransaction {
-                from: source,
-                seqno: transaction.seqno,
+            let raw_transaction = RawTransaction {
+                from: PeerId::from_bytes(&transaction.from).ok(),
+                seqno: if transaction.seqno == 0 {
+                    None
+                } else {
+                    Some(transaction.seqno)
+                },
                 data: transaction.data,
                 signature: if transaction.signature.is_empty() {
                     None
@@ -243,7 +284,22 @@ impl Decoder for DogCodec {
                 } else {
                     Some(transaction.key)
                 },
-            })
+                headers: transaction
+                    .headers
+                    .into_iter()
+                    .map(|header| (header.key, header.value))
+                    .collect(),
+                priority: transaction.priority,
+                hops: transaction.hops,
+                ttl: NonZeroU32::new(transaction.ttl),
+            };
+
+            match validation {
+                Ok(()) => transactions.push(raw_transaction),
+                Err(validation_error) => {
+                    invalid_transactions.push((raw_transaction, validation_error))
+                }
+            }
         }
 
         let mut control_msgs = Vec::new();
@@ -263,8 +319,26 @@ impl Decoder for DogCodec {
                 .into_iter()
                 .map(|_| ControlAction::ResetRoute(ResetRoute {}));
 
+            let sync_request_msgs = control
+                .sync_request
+                .into_iter()
+                .map(|sync_request| ControlAction::SyncRequest(sync_request.into()));
+
+            let sync_summary_msgs = control
+                .sync_summary
+                .into_iter()
+                .map(|sync_summary| ControlAction::SyncSummary(sync_summary.into()));
+
+            let reconcile_msgs = control
+                .reconcile
+                .into_iter()
+                .map(|reconcile| ControlAction::Reconcile(reconcile.into()));
+
             control_msgs.extend(have_tx_msgs);
             control_msgs.extend(reset_route_msgs);
+            control_msgs.extend(sync_request_msgs);
+            control_msgs.extend(sync_summary_msgs);
+            control_msgs.extend(reconcile_msgs);
         }
 
         Ok(Some(HandlerEvent::Transaction {
@@ -276,3 +350,219 @@ impl Decoder for DogCodec {
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use libp2p::identity::Keypair;
+
+    use super::*;
+
+    fn transaction(keypair: Option<&Keypair>, seqno: u64, signed: bool) -> proto::Transaction {
+        use quick_protobuf::MessageWrite;
+
+        let mut transaction = proto::Transaction {
+            from: keypair
+                .map(|keypair| keypair.public().to_peer_id().to_bytes())
+                .unwrap_or_default(),
+            seqno,
+            data: b"data".to_vec(),
+            headers: vec![],
+            priority: 0,
+            signature: vec![],
+            key: vec![],
+            hops: 0,
+            ttl: 0,
+        };
+
+        if let (Some(keypair), true) = (keypair, signed) {
+            let mut buf = Vec::with_capacity(transaction.get_size());
+            transaction
+                .write_message(&mut Writer::new(&mut buf))
+                .expect("Encoding to succeed");
+            let mut signature_bytes = SIGNING_PREFIX.to_vec();
+            signature_bytes.extend_from_slice(&buf);
+            transaction.signature = keypair.sign(&signature_bytes).expect("Signing to succeed");
+        }
+
+        transaction
+    }
+
+    fn validate(
+        validation_mode: ValidationMode,
+        transaction: &proto::Transaction,
+    ) -> Result<(), ValidationError> {
+        DogCodec::new(DEFAULT_MAX_TRANSMIT_SIZE, validation_mode).validate_transaction(transaction)
+    }
+
+    #[test]
+    fn strict_validation() {
+        let keypair = Keypair::generate_ed25519();
+
+        assert_eq!(
+            validate(
+                ValidationMode::Strict,
+                &transaction(Some(&keypair), 1, true)
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            validate(
+                ValidationMode::Strict,
+                &transaction(Some(&keypair), 1, false)
+            ),
+            Err(ValidationError::MissingSignature)
+        );
+        assert_eq!(
+            validate(
+                ValidationMode::Strict,
+                &transaction(Some(&keypair), 0, true)
+            ),
+            Err(ValidationError::EmptySequenceNumber)
+        );
+        assert_eq!(
+            validate(ValidationMode::Strict, &transaction(None, 0, false)),
+            Err(ValidationError::InvalidPeerId)
+        );
+
+        let mut tampered = transaction(Some(&keypair), 1, true);
+        tampered.data = b"tampered".to_vec();
+        assert_eq!(
+            validate(ValidationMode::Strict, &tampered),
+            Err(ValidationError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn permissive_validation() {
+        let keypair = Keypair::generate_ed25519();
+
+        assert_eq!(
+            validate(ValidationMode::Permissive, &transaction(None, 0, false)),
+            Ok(())
+        );
+        assert_eq!(
+            validate(
+                ValidationMode::Permissive,
+                &transaction(Some(&keypair), 0, false)
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            validate(
+                ValidationMode::Permissive,
+                &transaction(Some(&keypair), 1, true)
+            ),
+            Ok(())
+        );
+
+        let mut invalid_author = transaction(None, 1, false);
+        invalid_author.from = b"invalid".to_vec();
+        assert_eq!(
+            validate(ValidationMode::Permissive, &invalid_author),
+            Err(ValidationError::InvalidPeerId)
+        );
+
+        let mut tampered = transaction(Some(&keypair), 1, true);
+        tampered.data = b"tampered".to_vec();
+        assert_eq!(
+            validate(ValidationMode::Permissive, &tampered),
+            Err(ValidationError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn anonymous_validation() {
+        let keypair = Keypair::generate_ed25519();
+
+        assert_eq!(
+            validate(ValidationMode::Anonymous, &transaction(None, 0, false)),
+            Ok(())
+        );
+        assert_eq!(
+            validate(
+                ValidationMode::Anonymous,
+                &transaction(Some(&keypair), 1, true)
+            ),
+            Err(ValidationError::SignaturePresent)
+        );
+        assert_eq!(
+            validate(ValidationMode::Anonymous, &transaction(None, 1, false)),
+            Err(ValidationError::SequenceNumberPresent)
+        );
+        assert_eq!(
+            validate(
+                ValidationMode::Anonymous,
+                &transaction(Some(&keypair), 0, false)
+            ),
+            Err(ValidationError::AuthorPresent)
+        );
+    }
+
+    #[test]
+    fn author_validation() {
+        let keypair = Keypair::generate_ed25519();
+
+        assert_eq!(
+            validate(
+                ValidationMode::Author,
+                &transaction(Some(&keypair), 1, false)
+            ),
+            Ok(())
+        );
+
+        // The signature is not checked
+        let mut tampered = transaction(Some(&keypair), 1, true);
+        tampered.data = b"tampered".to_vec();
+        assert_eq!(validate(ValidationMode::Author, &tampered), Ok(()));
+
+        assert_eq!(
+            validate(
+                ValidationMode::Author,
+                &transaction(Some(&keypair), 0, false)
+            ),
+            Err(ValidationError::EmptySequenceNumber)
+        );
+        assert_eq!(
+            validate(ValidationMode::Author, &transaction(None, 1, false)),
+            Err(ValidationError::InvalidPeerId)
+        );
+    }
+
+    #[test]
+    fn none_validation() {
+        let keypair = Keypair::generate_ed25519();
+
+        assert_eq!(
+            validate(ValidationMode::None, &transaction(Some(&keypair), 0, false)),
+            Ok(())
+        );
+
+        let mut tampered = transaction(Some(&keypair), 1, true);
+        tampered.data = b"tampered".to_vec();
+        assert_eq!(validate(ValidationMode::None, &tampered), Ok(()));
+
+        // The author is still checked
+        assert_eq!(
+            validate(ValidationMode::None, &transaction(None, 0, false)),
+            Err(ValidationError::InvalidPeerId)
+        );
+    }
+
+    #[test]
+    fn unsorted_headers() {
+        let keypair = Keypair::generate_ed25519();
+
+        let mut transaction = transaction(Some(&keypair), 1, false);
+        transaction.headers = ["b", "a"]
+            .into_iter()
+            .map(|key| proto::TransactionHeader {
+                key: key.to_string(),
+                value: vec![],
+            })
+            .collect();
+        assert_eq!(
+            validate(ValidationMode::None, &transaction),
+            Err(ValidationError::InvalidHeaders)
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use std::{collections::VecDeque, time::Duration};
+
+use fnv::FnvHashMap;
+use web_time::Instant;
+
+use crate::types::{RawTransaction, TransactionId};
+
+/// A transaction published by the local node.
+pub(crate) struct PublishedTransaction {
+    /// The transaction as sent on the wire.
+    pub(crate) raw_transaction: RawTransaction,
+    /// The number of automatic retries performed so far.
+    pub(crate) retries: usize,
+}
+
+/// A bounded store of the last transactions published by the local node, allowing them to be
+/// sent again with the same sequence number and signature.
+pub(crate) struct PublishedTransactions {
+    /// The maximum number of transactions in the store.
+    capacity: usize,
+    /// Map of transaction ids to transactions.
+    transactions: FnvHashMap<TransactionId, PublishedTransaction>,
+    /// List of transaction ids in order of insertion.
+    list: VecDeque<TransactionId>,
+}
+
+impl PublishedTransactions {
+    pub(crate) fn new(capacity: usize) -> Self {
+        PublishedTransactions {
+            capacity,
+            transactions: FnvHashMap::default(),
+            list: VecDeque::new(),
+        }
+    }
+
+    /// Stores a published transaction, evicting the oldest one if the store is full.
+    pub(crate) fn insert(&mut self, tx_id: TransactionId, raw_transaction: RawTransaction) {
+        if self.capacity == 0 || self.transactions.contains_key(&tx_id) {
+            return;
+        }
+
+        while self.list.len() >= self.capacity {
+            if let Some(oldest) = self.list.pop_front() {
+                self.transactions.remove(&oldest);
+            }
+        }
+
+        self.list.push_back(tx_id.clone());
+        self.transactions.insert(
+            tx_id,
+            PublishedTransaction {
+                raw_transaction,
+                retries: 0,
+            },
+        );
+    }
+
+    pub(crate) fn get(&self, tx_id: &TransactionId) -> Option<&PublishedTransaction> {
+        self.transactions.get(tx_id)
+    }
+
+    pub(crate) fn get_mut(&mut self, tx_id: &TransactionId) -> Option<&mut PublishedTransaction> {
+        self.transactions.get_mut(tx_id)
+    }
+}
+
+/// A bounded buffer of transactions published while no DOG peer was connected, waiting for the
+/// first one to connect to be sent.
+pub(crate) struct PublishBuffer {
+    /// The maximum number of transactions in the buffer.
+    capacity: usize,
+    /// The time a transaction can wait in the buffer before it expires.
+    ttl: Duration,
+    /// Buffered transactions in order of insertion, along with their expiration.
+    list: VecDeque<(TransactionId, RawTransaction, Instant)>,
+}
+
+impl PublishBuffer {
+    pub(crate) fn new(capacity: usize, ttl: Duration) -> Self {
+        PublishBuffer {
+            capacity,
+            ttl,
+            list: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Buffers a transaction, returning the id of the oldest transaction if it had to be evicted
+    /// to make room.
+    pub(crate) fn push(
+        &mut self,
+        tx_id: TransactionId,
+        raw_transaction: RawTransaction,
+    ) -> Option<TransactionId> {
+        let evicted = if self.list.len() >= self.capacity {
+            self.list.pop_front().map(|(tx_id, _, _)| tx_id)
+        } else {
+            None
+        };
+
+        self.list
+            .push_back((tx_id, raw_transaction, Instant::now() + self.ttl));
+
+        evicted
+    }
+
+    /// Removes the expired transactions from the buffer and returns their ids.
+    pub(crate) fn remove_expired(&mut self) -> Vec<TransactionId> {
+        let now = Instant::now();
+        let mut expired = Vec::new();
+
+        while let Some((_, _, expiration)) = self.list.front() {
+            if *expiration > now {
+                break;
+            }
+            if let Some((tx_id, _, _)) = self.list.pop_front() {
+                expired.push(tx_id);
+            }
+        }
+
+        expired
+    }
+
+    /// Removes all the transactions from the buffer.
+    pub(crate) fn drain(&mut self) -> impl Iterator<Item = (TransactionId, RawTransaction)> + '_ {
+        self.list
+            .drain(..)
+            .map(|(tx_id, raw_transaction, _)| (tx_id, raw_transaction))
+    }
+}
@@ -0,0 +1,84 @@
+use std::future::Future;
+
+use futures::{
+    channel::{mpsc, oneshot},
+    SinkExt,
+};
+
+use crate::{
+    error::PublishError,
+    types::{PublishOptions, TransactionId},
+};
+
+/// A request to publish a transaction, sent from a [`DogPublisher`] to the behaviour.
+#[derive(Debug)]
+pub(crate) struct PublishRequest {
+    /// The data to publish, before the outbound transform.
+    pub(crate) data: Vec<u8>,
+    /// The options passed to the outbound transform.
+    pub(crate) options: PublishOptions,
+    /// The channel on which the outcome of the publication is reported.
+    pub(crate) response: oneshot::Sender<Result<TransactionId, PublishError>>,
+}
+
+/// A cloneable handle to publish transactions without holding a mutable reference to the
+/// [`crate::Behaviour`], e.g. from another task than the one driving the swarm.
+///
+/// Requests are queued and processed when the swarm polls the behaviour. In particular, the
+/// outbound transform runs and a [`crate::TransactionSigner`] is awaited there without blocking
+/// the swarm task. Transactions are published in the order of the requests. The queue is bounded
+/// by [`crate::Config::publish_request_queue_len`].
+#[derive(Debug, Clone)]
+pub struct DogPublisher {
+    sender: mpsc::Sender<PublishRequest>,
+}
+
+impl DogPublisher {
+    pub(crate) fn new(sender: mpsc::Sender<PublishRequest>) -> Self {
+        Self { sender }
+    }
+
+    /// Queues a transaction to be published. The returned future resolves once the transaction has
+    /// been queued to peers, with the same outcome as [`crate::Behaviour::publish`].
+    ///
+    /// The request is queued immediately if there is room, so that requests made in a row keep
+    /// their order. Otherwise, the returned future waits for room first.
+    pub fn publish(
+        &self,
+        data: impl Into<Vec<u8>>,
+    ) -> impl Future<Output = Result<TransactionId, PublishError>> + Send + 'static {
+        self.publish_with_options(data, PublishOptions::default())
+    }
+
+    /// Queues a transaction to be published with options passed to the outbound transform. See
+    /// [`DogPublisher::publish`].
+    pub fn publish_with_options(
+        &self,
+        data: impl Into<Vec<u8>>,
+        options: PublishOptions,
+    ) -> impl Future<Output = Result<TransactionId, PublishError>> + Send + 'static {
+        let (response, receiver) = oneshot::channel();
+        let request = PublishRequest {
+            data: data.into(),
+            options,
+            response,
+        };
+        let mut sender = self.sender.clone();
+        let pending = match sender.try_send(request) {
+            Ok(()) => Ok(None),
+            Err(e) if e.is_full() => Ok(Some(e.into_inner())),
+            Err(_) => Err(PublishError::BehaviourDropped),
+        };
+
+        async move {
+            if let Some(request) = pending? {
+                if sender.send(request).await.is_err() {
+                    return Err(PublishError::BehaviourDropped);
+                }
+            }
+            receiver
+                .await
+                .unwrap_or(Err(PublishError::BehaviourDropped))
+        }
+    }
+}
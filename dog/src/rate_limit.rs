@@ -0,0 +1,92 @@
+use web_time::Instant;
+
+use crate::config::Config;
+
+/// A token bucket refilled at a constant rate, allowing bursts of up to one second worth of
+/// tokens.
+struct TokenBucket {
+    /// The number of tokens added per second, which is also the capacity of the bucket.
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64) -> Self {
+        TokenBucket {
+            rate,
+            tokens: rate,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Refills the bucket and returns whether `amount` tokens are available.
+    fn available(&mut self, amount: f64) -> bool {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.last_refill = now;
+        self.tokens >= amount
+    }
+
+    /// Consumes `amount` tokens if available.
+    fn try_consume(&mut self, amount: f64) -> bool {
+        if !self.available(amount) {
+            return false;
+        }
+        self.tokens -= amount;
+        true
+    }
+}
+
+/// The rate limits applied to the transactions and control messages received from a peer.
+pub(crate) struct PeerRateLimiter {
+    transactions: Option<TokenBucket>,
+    bytes: Option<TokenBucket>,
+    control_messages: Option<TokenBucket>,
+}
+
+impl PeerRateLimiter {
+    pub(crate) fn new(config: &Config) -> Self {
+        PeerRateLimiter {
+            transactions: config
+                .peer_max_transactions_per_second()
+                .map(TokenBucket::new),
+            bytes: config.peer_max_bytes_per_second().map(TokenBucket::new),
+            control_messages: config
+                .peer_max_control_messages_per_second()
+                .map(TokenBucket::new),
+        }
+    }
+
+    /// Returns whether a transaction of `bytes` bytes can be accepted from the peer. Tokens are
+    /// only consumed if the transaction is accepted.
+    pub(crate) fn allow_transaction(&mut self, bytes: usize) -> bool {
+        let bytes = bytes as f64;
+        let allowed = self
+            .transactions
+            .as_mut()
+            .is_none_or(|bucket| bucket.available(1.0))
+            && self
+                .bytes
+                .as_mut()
+                .is_none_or(|bucket| bucket.available(bytes));
+
+        if allowed {
+            if let Some(bucket) = self.transactions.as_mut() {
+                bucket.tokens -= 1.0;
+            }
+            if let Some(bucket) = self.bytes.as_mut() {
+                bucket.tokens -= bytes;
+            }
+        }
+        allowed
+    }
+
+    /// Returns whether a control message can be accepted from the peer.
+    pub(crate) fn allow_control_message(&mut self) -> bool {
+        self.control_messages
+            .as_mut()
+            .is_none_or(|bucket| bucket.try_consume(1.0))
+    }
+}
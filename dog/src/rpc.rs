@@ -12,130 +12,278 @@ use futures::{stream::Peekable, Stream, StreamExt};
 
 use crate::types::RpcOut;
 
+/// Scale applied to the cost of a transaction before dividing it by the weight of its class, to
+/// keep precision with integer virtual times.
+const WEIGHT_SCALE: u64 = 1 << 16;
+
 /// `RpcOut` sender that is priority aware.
+///
+/// Control messages are sent before any transaction. Transactions are queued by priority class,
+/// and own publishes are sent before forwards of the same class.
 #[derive(Debug)]
 pub(crate) struct Sender {
-    /// Capacity of the priority channel for `Publish` transactions.
-    priority_cap: usize,
-    len: Arc<AtomicUsize>,
-    pub(crate) priority_sender: async_channel::Sender<RpcOut>,
-    pub(crate) non_priority_sender: async_channel::Sender<RpcOut>,
-    priority_receiver: async_channel::Receiver<RpcOut>,
-    non_priority_receiver: async_channel::Receiver<RpcOut>,
+    /// Capacity of the queues for `Publish` transactions.
+    publish_cap: usize,
+    /// Capacity of the queues for `Forward` transactions.
+    forward_cap: usize,
+    publish_len: Arc<AtomicUsize>,
+    forward_len: Arc<AtomicUsize>,
+    control_sender: async_channel::Sender<RpcOut>,
+    control_receiver: async_channel::Receiver<RpcOut>,
+    classes: Vec<ClassChannels>,
+    /// The highest class index used by `Forward` transactions.
+    max_forward_class: usize,
+}
+
+/// The channels of a single priority class.
+#[derive(Debug)]
+struct ClassChannels {
+    weight: u32,
+    publish_sender: async_channel::Sender<RpcOut>,
+    forward_sender: async_channel::Sender<RpcOut>,
+    publish_receiver: async_channel::Receiver<RpcOut>,
+    forward_receiver: async_channel::Receiver<RpcOut>,
 }
 
 impl Sender {
-    /// Create a Rpc Sender.
-    pub(crate) fn new(cap: usize) -> Sender {
-        // We intentionally do not bound the channel, as we still need to send control messages
+    /// Create a Rpc Sender with a priority class for each of the given weights. Forwarded
+    /// transactions use at most the class `max_forwarded_priority`, if any.
+    pub(crate) fn new(
+        cap: usize,
+        class_weights: &[u32],
+        max_forwarded_priority: Option<u32>,
+    ) -> Sender {
+        // We intentionally do not bound the channels, as we still need to send control messages
         // such as `HaveTx` and `ResetRoute`.
         // That's also why we define `cap` and divide it by two.
-        // to ensure there is capacity for both priority and non-priority messages.
-        let len = Arc::new(AtomicUsize::new(0));
-        let (priority_sender, priority_receiver) = async_channel::unbounded();
-        let (non_priority_sender, non_priority_receiver) = async_channel::bounded(cap / 2);
+        // to ensure there is capacity for both publish and forward messages.
+        let (control_sender, control_receiver) = async_channel::unbounded();
+        let classes = class_weights
+            .iter()
+            .map(|&weight| {
+                let (publish_sender, publish_receiver) = async_channel::unbounded();
+                let (forward_sender, forward_receiver) = async_channel::unbounded();
+                ClassChannels {
+                    weight,
+                    publish_sender,
+                    forward_sender,
+                    publish_receiver,
+                    forward_receiver,
+                }
+            })
+            .collect();
         Sender {
-            priority_cap: cap / 2,
-            len,
-            priority_sender,
-            non_priority_sender,
-            priority_receiver,
-            non_priority_receiver,
+            publish_cap: cap / 2,
+            forward_cap: cap / 2,
+            publish_len: Arc::new(AtomicUsize::new(0)),
+            forward_len: Arc::new(AtomicUsize::new(0)),
+            control_sender,
+            control_receiver,
+            max_forward_class: max_forwarded_priority
+                .map_or(usize::MAX, |priority| priority as usize)
+                .min(class_weights.len() - 1),
+            classes,
         }
     }
 
     /// Create a new Receiver to the sender.
     pub(crate) fn new_receiver(&self) -> Receiver {
         Receiver {
-            priority_queue_len: self.len.clone(),
-            priority_receiver: Box::pin(self.priority_receiver.clone().peekable()),
-            non_priority_receiver: Box::pin(self.non_priority_receiver.clone().peekable()),
+            publish_queue_len: self.publish_len.clone(),
+            forward_queue_len: self.forward_len.clone(),
+            control_receiver: Box::pin(self.control_receiver.clone().peekable()),
+            classes: self
+                .classes
+                .iter()
+                .map(|class| ClassQueue {
+                    weight: class.weight.into(),
+                    next_start: None,
+                    last_finish: 0,
+                    publish_receiver: Box::pin(class.publish_receiver.clone().peekable()),
+                    forward_receiver: Box::pin(class.forward_receiver.clone().peekable()),
+                })
+                .collect(),
+            virtual_time: 0,
         }
     }
 
+    /// Returns `true` if no more `Publish` transactions can be queued.
+    pub(crate) fn is_publish_queue_full(&self) -> bool {
+        self.publish_len.load(Ordering::Relaxed) >= self.publish_cap
+    }
+
     pub(crate) fn send_transaction(&self, rpc: RpcOut) -> Result<(), RpcOut> {
-        if let RpcOut::Publish { .. } = rpc {
-            let len = self.len.load(Ordering::Relaxed);
-            if len >= self.priority_cap {
-                return Err(rpc);
+        let (len, cap) = match rpc {
+            RpcOut::Publish { .. } => (&self.publish_len, self.publish_cap),
+            RpcOut::Forward { .. } => (&self.forward_len, self.forward_cap),
+            _ => {
+                return self
+                    .control_sender
+                    .try_send(rpc)
+                    .map_err(|err| err.into_inner())
             }
-            self.len.store(len + 1, Ordering::Relaxed);
+        };
+
+        let queued = len.load(Ordering::Relaxed);
+        if queued >= cap {
+            return Err(rpc);
         }
+        len.store(queued + 1, Ordering::Relaxed);
+
+        let class =
+            &self.classes[class_index(&rpc, self.classes.len() - 1, self.max_forward_class)];
         let sender = match rpc {
-            RpcOut::Publish { .. } | RpcOut::HaveTx(_) | RpcOut::ResetRoute(_) => {
-                &self.priority_sender
-            }
-            RpcOut::Forward { .. } => &self.non_priority_sender,
+            RpcOut::Publish { .. } => &class.publish_sender,
+            _ => &class.forward_sender,
         };
         sender.try_send(rpc).map_err(|err| err.into_inner())
     }
+}
 
-    // pub(crate) fn priority_queue_len(&self) -> usize {
-    //     self.len.load(Ordering::Relaxed)
-    // }
+/// Returns the index of the priority class of a transaction, transactions of an unknown class
+/// using the last class and forwarded transactions using at most `max_forward_class`.
+fn class_index(rpc: &RpcOut, last_class: usize, max_forward_class: usize) -> usize {
+    match rpc {
+        RpcOut::Publish { tx, .. } => (tx.priority as usize).min(last_class),
+        RpcOut::Forward { tx, .. } => (tx.priority as usize).min(max_forward_class),
+        _ => 0,
+    }
+}
 
-    // pub(crate) fn non_priority_queue_len(&self) -> usize {
-    //     self.non_priority_receiver.len()
-    // }
+/// Returns the cost of sending a transaction, used to share the bandwidth between the priority
+/// classes.
+fn cost(rpc: &RpcOut) -> u64 {
+    match rpc {
+        RpcOut::Publish { tx, .. } | RpcOut::Forward { tx, .. } => tx.data.len().max(1) as u64,
+        _ => 1,
+    }
+}
+
+/// The queues of a single priority class in a [`Receiver`].
+#[derive(Debug)]
+struct ClassQueue {
+    weight: u64,
+    /// The virtual start time of the next transaction of the class, while the class has queued
+    /// transactions.
+    next_start: Option<u64>,
+    /// The virtual finish time of the last transaction sent from this class.
+    last_finish: u64,
+    publish_receiver: Pin<Box<Peekable<async_channel::Receiver<RpcOut>>>>,
+    forward_receiver: Pin<Box<Peekable<async_channel::Receiver<RpcOut>>>>,
+}
+
+impl ClassQueue {
+    /// Returns the virtual finish time of the next transaction of the class, if any.
+    fn poll_next_finish(&mut self, cx: &mut Context<'_>, virtual_time: u64) -> Option<u64> {
+        let next = match self.publish_receiver.as_mut().poll_peek(cx) {
+            Poll::Ready(Some(rpc)) => rpc,
+            _ => match self.forward_receiver.as_mut().poll_peek(cx) {
+                Poll::Ready(Some(rpc)) => rpc,
+                _ => {
+                    self.next_start = None;
+                    return None;
+                }
+            },
+        };
+        // A class that was idle starts at the current virtual time, so that it does not get
+        // credit for the time it had nothing to send.
+        let start = *self
+            .next_start
+            .get_or_insert(self.last_finish.max(virtual_time));
+        Some(start + cost(next) * WEIGHT_SCALE / self.weight)
+    }
 }
 
 /// `RpcOut` receiver that is priority aware.
+///
+/// Transactions are scheduled between the priority classes with self-clocked weighted fair
+/// queuing: each transaction gets a virtual finish time, advancing by its cost divided by the
+/// weight of its class, and the transaction with the earliest finish time is sent first.
 #[derive(Debug)]
 pub struct Receiver {
-    pub(crate) priority_queue_len: Arc<AtomicUsize>,
-    pub(crate) priority_receiver: Pin<Box<Peekable<async_channel::Receiver<RpcOut>>>>,
-    pub(crate) non_priority_receiver: Pin<Box<Peekable<async_channel::Receiver<RpcOut>>>>,
+    publish_queue_len: Arc<AtomicUsize>,
+    forward_queue_len: Arc<AtomicUsize>,
+    control_receiver: Pin<Box<Peekable<async_channel::Receiver<RpcOut>>>>,
+    classes: Vec<ClassQueue>,
+    /// The virtual finish time of the last transaction sent.
+    virtual_time: u64,
 }
 
 impl Receiver {
-    // Peek the next transaction in the queues and return it if its timeout has elapsed.
+    /// Decrements the length of the queue the transaction has been taken from.
+    fn dequeued(&self, rpc: &RpcOut) {
+        match rpc {
+            RpcOut::Publish { .. } => {
+                self.publish_queue_len.fetch_sub(1, Ordering::Relaxed);
+            }
+            RpcOut::Forward { .. } => {
+                self.forward_queue_len.fetch_sub(1, Ordering::Relaxed);
+            }
+            _ => {}
+        }
+    }
+
+    // Peek the next transactions in the queues and return one if its timeout has elapsed.
     // Returns `None` if there aren't any more transactions on the stream or none is stale.
     pub(crate) fn poll_stale(&mut self, cx: &mut Context<'_>) -> Poll<Option<RpcOut>> {
-        let priority = match self.priority_receiver.as_mut().poll_peek_mut(cx) {
-            Poll::Ready(Some(RpcOut::Publish {
-                tx: _,
-                ref mut timeout,
-            })) => {
-                if Pin::new(timeout).poll(cx).is_ready() {
-                    let dropped = futures::ready!(self.priority_receiver.poll_next_unpin(cx))
-                        .expect("There should be a transaction");
-                    return Poll::Ready(Some(dropped));
-                }
-                Poll::Ready(None)
-            }
-            poll => poll,
-        };
+        let mut pending = false;
+        let mut dropped = None;
 
-        let non_priority = match self.non_priority_receiver.as_mut().poll_peek_mut(cx) {
-            Poll::Ready(Some(RpcOut::Forward {
-                tx: _,
-                ref mut timeout,
-            })) => {
-                if Pin::new(timeout).poll(cx).is_ready() {
-                    let dropped = futures::ready!(self.non_priority_receiver.poll_next_unpin(cx))
-                        .expect("There should be a transaction");
-                    return Poll::Ready(Some(dropped));
+        'classes: for class in self.classes.iter_mut() {
+            for receiver in [&mut class.publish_receiver, &mut class.forward_receiver] {
+                match receiver.as_mut().poll_peek_mut(cx) {
+                    Poll::Ready(Some(
+                        RpcOut::Publish {
+                            ref mut timeout, ..
+                        }
+                        | RpcOut::Forward {
+                            ref mut timeout, ..
+                        },
+                    )) => {
+                        if Pin::new(timeout).poll(cx).is_ready() {
+                            dropped = Some(
+                                futures::ready!(receiver.poll_next_unpin(cx))
+                                    .expect("There should be a transaction"),
+                            );
+                            break 'classes;
+                        }
+                    }
+                    Poll::Ready(_) => {}
+                    Poll::Pending => pending = true,
                 }
-                Poll::Ready(None)
             }
-            poll => poll,
-        };
+        }
 
-        match (priority, non_priority) {
-            (Poll::Ready(None), Poll::Ready(None)) => Poll::Ready(None),
-            _ => Poll::Pending,
+        if let Some(dropped) = dropped {
+            self.dequeued(&dropped);
+            return Poll::Ready(Some(dropped));
+        }
+
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(None)
         }
     }
 
-    /// Poll queues and return true if both are empty.
+    /// Poll queues and return true if all are empty.
     pub(crate) fn poll_is_empty(&mut self, cx: &mut Context<'_>) -> bool {
-        matches!(
-            (
-                self.priority_receiver.as_mut().poll_peek(cx),
-                self.non_priority_receiver.as_mut().poll_peek(cx),
-            ),
-            (Poll::Ready(None), Poll::Ready(None))
-        )
+        if !matches!(
+            self.control_receiver.as_mut().poll_peek(cx),
+            Poll::Ready(None)
+        ) {
+            return false;
+        }
+
+        self.classes.iter_mut().all(|class| {
+            matches!(
+                (
+                    class.publish_receiver.as_mut().poll_peek(cx),
+                    class.forward_receiver.as_mut().poll_peek(cx),
+                ),
+                (Poll::Ready(None), Poll::Ready(None))
+            )
+        })
     }
 }
 
@@ -146,12 +294,112 @@ impl Stream for Receiver {
         mut self: Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> Poll<Option<Self::Item>> {
-        if let Poll::Ready(rpc) = Pin::new(&mut self.priority_receiver).poll_next(cx) {
-            if let Some(RpcOut::Publish { .. }) = rpc {
-                self.priority_queue_len.fetch_sub(1, Ordering::Relaxed);
-            }
+        if let Poll::Ready(rpc) = Pin::new(&mut self.control_receiver).poll_next(cx) {
             return Poll::Ready(rpc);
         }
-        Pin::new(&mut self.non_priority_receiver).poll_next(cx)
+
+        let virtual_time = self.virtual_time;
+        let next = self
+            .classes
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, class)| {
+                class
+                    .poll_next_finish(cx, virtual_time)
+                    .map(|finish| (finish, index))
+            })
+            .min();
+
+        let Some((finish, index)) = next else {
+            return Poll::Pending;
+        };
+
+        let class = &mut self.classes[index];
+        let rpc = match class.publish_receiver.as_mut().poll_next(cx) {
+            Poll::Ready(Some(rpc)) => rpc,
+            _ => futures::ready!(class.forward_receiver.as_mut().poll_next(cx))
+                .expect("There should be a transaction"),
+        };
+        class.last_finish = finish;
+        class.next_start = Some(finish);
+        self.virtual_time = finish;
+        self.dequeued(&rpc);
+
+        Poll::Ready(Some(rpc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, time::Duration};
+
+    use futures::FutureExt;
+    use futures_timer::Delay;
+
+    use super::*;
+    use crate::types::RawTransaction;
+
+    fn forward(priority: u32) -> RpcOut {
+        RpcOut::Forward {
+            tx: RawTransaction {
+                from: None,
+                seqno: None,
+                data: vec![0; 100],
+                headers: BTreeMap::new(),
+                priority,
+                signature: None,
+                key: None,
+                hops: 0,
+                ttl: None,
+            },
+            timeout: Delay::new(Duration::from_secs(60)),
+        }
+    }
+
+    fn priority(rpc: &RpcOut) -> u32 {
+        match rpc {
+            RpcOut::Forward { tx, .. } | RpcOut::Publish { tx, .. } => tx.priority,
+            _ => panic!("Expected a transaction"),
+        }
+    }
+
+    #[test]
+    fn weighted_interleaving() {
+        let sender = Sender::new(1000, &[1, 3], None);
+        let mut receiver = sender.new_receiver();
+
+        for _ in 0..100 {
+            sender.send_transaction(forward(0)).unwrap();
+            sender.send_transaction(forward(1)).unwrap();
+        }
+
+        // While both classes are backlogged, the second class gets three transactions for each
+        // transaction of the first one.
+        let sent = (0..100)
+            .map(|_| {
+                priority(
+                    &receiver
+                        .next()
+                        .now_or_never()
+                        .flatten()
+                        .expect("A transaction should be queued"),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for window in sent.chunks(4) {
+            assert_eq!(window.iter().filter(|priority| **priority == 1).count(), 3);
+        }
+    }
+
+    #[test]
+    fn max_forwarded_priority() {
+        let sender = Sender::new(1000, &[1, 1, 1], Some(1));
+
+        sender.send_transaction(forward(2)).unwrap();
+        sender.send_transaction(forward(5)).unwrap();
+
+        assert_eq!(sender.classes[1].forward_receiver.len(), 2);
+        assert!(sender.classes[2].forward_receiver.is_empty());
     }
 }
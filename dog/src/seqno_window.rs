@@ -0,0 +1,226 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use fnv::FnvHashMap;
+use libp2p::PeerId;
+use web_time::Instant;
+
+const WORD_BITS: u64 = u64::BITS as u64;
+
+/// A sliding window over the last sequence numbers seen from a single author.
+///
+/// The window is a ring bitmap indexed by sequence number, anchored on the highest sequence
+/// number seen so far.
+struct Window {
+    /// The highest sequence number seen from the author.
+    highest: u64,
+    /// One bit per sequence number in the window.
+    bitmap: Vec<u64>,
+    /// The time after which the window can be discarded.
+    expiration: Instant,
+    /// The position of the author in the order of last use.
+    last_use: u64,
+}
+
+impl Window {
+    fn new(seqno: u64, words: usize, expiration: Instant, last_use: u64) -> Self {
+        let mut window = Window {
+            highest: seqno,
+            bitmap: vec![0; words],
+            expiration,
+            last_use,
+        };
+        window.set(seqno);
+        window
+    }
+
+    fn bits(&self) -> u64 {
+        self.bitmap.len() as u64 * WORD_BITS
+    }
+
+    fn position(&self, seqno: u64) -> (usize, u64) {
+        let index = seqno % self.bits();
+        ((index / WORD_BITS) as usize, 1 << (index % WORD_BITS))
+    }
+
+    fn is_set(&self, seqno: u64) -> bool {
+        let (word, mask) = self.position(seqno);
+        self.bitmap[word] & mask != 0
+    }
+
+    fn set(&mut self, seqno: u64) {
+        let (word, mask) = self.position(seqno);
+        self.bitmap[word] |= mask;
+    }
+
+    fn clear(&mut self, seqno: u64) {
+        let (word, mask) = self.position(seqno);
+        self.bitmap[word] &= !mask;
+    }
+
+    /// Moves the window forward so that `seqno` becomes the highest sequence number.
+    fn advance(&mut self, seqno: u64) {
+        if seqno - self.highest >= self.bits() {
+            self.bitmap.iter_mut().for_each(|word| *word = 0);
+        } else {
+            for skipped in self.highest + 1..=seqno {
+                self.clear(skipped);
+            }
+        }
+        self.highest = seqno;
+    }
+}
+
+/// Tracks, for each author, a sliding window of the last sequence numbers seen in order to
+/// detect replayed transactions.
+pub(crate) struct SequenceNumberWindows {
+    /// The number of sequence numbers below the highest one that are still accepted.
+    size: u64,
+    /// The number of 64-bit words needed to hold `size` bits.
+    words: usize,
+    /// Map of authors to their window.
+    windows: FnvHashMap<PeerId, Window>,
+    /// Authors in order of their last transaction, which is also the order of expiration.
+    list: BTreeMap<u64, PeerId>,
+    /// The counter used to order the authors by last use.
+    next_use: u64,
+    /// The time a window remains after the last transaction of its author.
+    ttl: Duration,
+    /// The maximum number of authors tracked.
+    max_authors: usize,
+}
+
+impl SequenceNumberWindows {
+    pub(crate) fn new(size: usize, ttl: Duration, max_authors: usize) -> Self {
+        SequenceNumberWindows {
+            size: size as u64,
+            words: size.div_ceil(WORD_BITS as usize).max(1),
+            windows: FnvHashMap::default(),
+            list: BTreeMap::new(),
+            next_use: 0,
+            ttl,
+            max_authors,
+        }
+    }
+
+    fn remove_expired_windows(&mut self, now: Instant) {
+        while let Some(entry) = self.list.first_entry() {
+            if self.windows[entry.get()].expiration > now {
+                break;
+            }
+            self.windows.remove(&entry.remove());
+        }
+    }
+
+    /// Removes the window of the least recently seen author.
+    fn evict_window(&mut self) {
+        if let Some((_, author)) = self.list.pop_first() {
+            tracing::debug!(%author, "Too many authors tracked, evicting sequence numbers");
+            self.windows.remove(&author);
+        }
+    }
+
+    /// Records the sequence number of a transaction from `author`. Returns `false` if the
+    /// sequence number has already been seen or is too old to be tracked, `true` otherwise.
+    pub(crate) fn insert(&mut self, author: PeerId, seqno: u64) -> bool {
+        let now = Instant::now();
+        self.remove_expired_windows(now);
+
+        let expiration = now + self.ttl;
+        let last_use = self.next_use;
+        self.next_use += 1;
+
+        let Some(window) = self.windows.get_mut(&author) else {
+            if self.windows.len() >= self.max_authors {
+                self.evict_window();
+            }
+            self.windows
+                .insert(author, Window::new(seqno, self.words, expiration, last_use));
+            self.list.insert(last_use, author);
+            return true;
+        };
+        window.expiration = expiration;
+        self.list.remove(&window.last_use);
+        self.list.insert(last_use, author);
+        window.last_use = last_use;
+
+        if seqno > window.highest {
+            window.advance(seqno);
+            window.set(seqno);
+            return true;
+        }
+
+        if window.highest - seqno >= self.size {
+            tracing::debug!(%author, seqno, "Sequence number is too old");
+            return false;
+        }
+
+        if window.is_set(seqno) {
+            tracing::debug!(%author, seqno, "Sequence number has already been seen");
+            return false;
+        }
+
+        window.set(seqno);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicate_in_window() {
+        let mut windows = SequenceNumberWindows::new(100, Duration::from_secs(60), 10);
+        let author = PeerId::random();
+
+        assert!(windows.insert(author, 10));
+        assert!(windows.insert(author, 12));
+        assert!(windows.insert(author, 11));
+        assert!(!windows.insert(author, 10));
+        assert!(!windows.insert(author, 11));
+        assert!(!windows.insert(author, 12));
+    }
+
+    #[test]
+    fn too_old() {
+        let mut windows = SequenceNumberWindows::new(100, Duration::from_secs(60), 10);
+        let author = PeerId::random();
+
+        assert!(windows.insert(author, 200));
+        assert!(windows.insert(author, 101));
+        assert!(!windows.insert(author, 100));
+        assert!(!windows.insert(author, 0));
+    }
+
+    #[test]
+    fn big_jump() {
+        let mut windows = SequenceNumberWindows::new(100, Duration::from_secs(60), 10);
+        let author = PeerId::random();
+
+        assert!(windows.insert(author, 1));
+        assert!(windows.insert(author, 2));
+        assert!(windows.insert(author, 1_000_000));
+        // The sequence numbers skipped by the jump have not been seen.
+        assert!(windows.insert(author, 999_999));
+        assert!(!windows.insert(author, 1_000_000));
+        assert!(!windows.insert(author, 2));
+        assert!(windows.insert(author, u64::MAX));
+        assert!(!windows.insert(author, 1_000_000));
+    }
+
+    #[test]
+    fn evict_least_recently_seen_author() {
+        let mut windows = SequenceNumberWindows::new(100, Duration::from_secs(60), 2);
+        let authors = [PeerId::random(), PeerId::random(), PeerId::random()];
+
+        assert!(windows.insert(authors[0], 1));
+        assert!(windows.insert(authors[1], 1));
+        assert!(windows.insert(authors[0], 2));
+        // The second author is the least recently seen.
+        assert!(windows.insert(authors[2], 1));
+
+        assert_eq!(windows.windows.len(), 2);
+        assert!(!windows.insert(authors[0], 1));
+        assert!(windows.insert(authors[1], 1));
+    }
+}
@@ -0,0 +1,43 @@
+use futures::future::{self, BoxFuture, FutureExt};
+use libp2p::identity::{Keypair, PublicKey};
+
+/// A general trait for signing published transactions with a key that does not need to live in
+/// the process, e.g. in a hardware security module or behind a remote signing service.
+///
+/// The author of the published transactions is derived from [`TransactionSigner::public_key`]
+/// and the signatures produced by [`TransactionSigner::sign`] are verified by remote peers
+/// against it, exactly as for [`crate::TransactionAuthenticity::Signed`].
+pub trait TransactionSigner: Send + Sync {
+    /// Returns the public key of the signer.
+    fn public_key(&self) -> PublicKey;
+
+    /// Signs the given bytes and returns the signature bytes.
+    fn sign(&self, msg: Vec<u8>) -> BoxFuture<'static, Result<Vec<u8>, std::io::Error>>;
+}
+
+impl std::fmt::Debug for dyn TransactionSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TransactionSigner({})", self.public_key().to_peer_id())
+    }
+}
+
+/// An in-process [`TransactionSigner`] backed by a [`Keypair`]. This is mostly useful for testing
+/// custom signing setups.
+#[derive(Debug, Clone)]
+pub struct KeypairSigner(Keypair);
+
+impl KeypairSigner {
+    pub fn new(keypair: Keypair) -> Self {
+        Self(keypair)
+    }
+}
+
+impl TransactionSigner for KeypairSigner {
+    fn public_key(&self) -> PublicKey {
+        self.0.public()
+    }
+
+    fn sign(&self, msg: Vec<u8>) -> BoxFuture<'static, Result<Vec<u8>, std::io::Error>> {
+        future::ready(self.0.sign(&msg).map_err(std::io::Error::other)).boxed()
+    }
+}
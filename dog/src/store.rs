@@ -0,0 +1,134 @@
+use std::{collections::VecDeque, time::Duration};
+
+use fnv::FnvHashMap;
+use web_time::Instant;
+
+use crate::types::{RawTransaction, Transaction, TransactionId};
+
+struct StoredTransaction {
+    raw_transaction: RawTransaction,
+    transaction: Transaction,
+    expiration: Instant,
+}
+
+impl StoredTransaction {
+    fn size(&self) -> usize {
+        self.raw_transaction.data.len() + self.transaction.data.len()
+    }
+}
+
+/// A bounded store of recently seen transactions, allowing them to be looked up by id.
+///
+/// Transactions are evicted in order of insertion when they expire, or when the store exceeds its
+/// maximum number of transactions or bytes.
+pub(crate) struct TransactionStore {
+    /// The maximum number of transactions in the store.
+    max_transactions: usize,
+    /// The maximum number of data bytes in the store.
+    max_bytes: usize,
+    /// The current number of data bytes in the store.
+    bytes: usize,
+    /// Map of transaction ids to transactions.
+    transactions: FnvHashMap<TransactionId, StoredTransaction>,
+    /// List of transaction ids in order of insertion, which is also the order of expiration.
+    list: VecDeque<TransactionId>,
+    /// The time transactions remain in the store.
+    ttl: Duration,
+}
+
+impl TransactionStore {
+    pub(crate) fn new(max_transactions: usize, max_bytes: usize, ttl: Duration) -> Self {
+        TransactionStore {
+            max_transactions,
+            max_bytes,
+            bytes: 0,
+            transactions: FnvHashMap::default(),
+            list: VecDeque::new(),
+            ttl,
+        }
+    }
+
+    fn remove_oldest(&mut self) {
+        if let Some(tx_id) = self.list.pop_front() {
+            if let Some(stored) = self.transactions.remove(&tx_id) {
+                self.bytes -= stored.size();
+            }
+        }
+    }
+
+    fn remove_expired(&mut self, now: Instant) {
+        while let Some(tx_id) = self.list.front() {
+            if self
+                .transactions
+                .get(tx_id)
+                .is_some_and(|stored| stored.expiration > now)
+            {
+                break;
+            }
+            self.remove_oldest();
+        }
+    }
+
+    /// Stores a transaction. Transactions larger than the maximum number of bytes are not stored.
+    pub(crate) fn insert(
+        &mut self,
+        tx_id: TransactionId,
+        raw_transaction: RawTransaction,
+        transaction: Transaction,
+    ) {
+        let now = Instant::now();
+        self.remove_expired(now);
+
+        let stored = StoredTransaction {
+            raw_transaction,
+            transaction,
+            expiration: now + self.ttl,
+        };
+        let size = stored.size();
+
+        if size > self.max_bytes || self.transactions.contains_key(&tx_id) {
+            return;
+        }
+
+        while self.list.len() >= self.max_transactions || self.bytes + size > self.max_bytes {
+            self.remove_oldest();
+        }
+
+        self.bytes += size;
+        self.list.push_back(tx_id.clone());
+        self.transactions.insert(tx_id, stored);
+    }
+
+    /// Returns the ids of the most recent transactions in the store, up to `limit`.
+    pub(crate) fn recent_ids(&self, limit: usize) -> Vec<TransactionId> {
+        let now = Instant::now();
+        self.list
+            .iter()
+            .rev()
+            .filter(|tx_id| {
+                self.transactions
+                    .get(*tx_id)
+                    .is_some_and(|stored| stored.expiration > now)
+            })
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the transaction with the given id as sent on the wire, if it is in the store and
+    /// has not expired.
+    pub(crate) fn get_raw(&self, tx_id: &TransactionId) -> Option<&RawTransaction> {
+        self.transactions
+            .get(tx_id)
+            .filter(|stored| stored.expiration > Instant::now())
+            .map(|stored| &stored.raw_transaction)
+    }
+
+    /// Returns the transaction with the given id if it is in the store and has not expired.
+    pub(crate) fn get(&self, tx_id: &TransactionId) -> Option<&Transaction> {
+        self.transactions
+            .get(tx_id)
+            .filter(|stored| stored.expiration > Instant::now())
+            .map(|stored| &stored.transaction)
+    }
+}
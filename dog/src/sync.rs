@@ -0,0 +1,158 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use libp2p::PeerId;
+use web_time::Instant;
+
+use crate::types::TransactionId;
+
+/// Tracks the catch-up syncs with our peers, both the ones we requested and the ones we serve.
+pub(crate) struct SyncState {
+    /// The minimum time between two summaries served to the same peer. Fetched transactions that
+    /// have not been received within this time are no longer expected.
+    min_interval: Duration,
+    /// Peers we have requested a summary from and that have not responded yet.
+    requested: HashSet<PeerId>,
+    /// Transactions fetched from each peer that have not been received yet, with the time after
+    /// which they are no longer expected.
+    fetching: HashMap<PeerId, HashMap<TransactionId, Instant>>,
+    /// The last time a summary was served to each peer.
+    served: HashMap<PeerId, Instant>,
+    /// Peers that have been served a summary and may fetch transactions once.
+    fetch_allowed: HashSet<PeerId>,
+}
+
+impl SyncState {
+    pub(crate) fn new(min_interval: Duration) -> Self {
+        SyncState {
+            min_interval,
+            requested: HashSet::new(),
+            fetching: HashMap::new(),
+            served: HashMap::new(),
+            fetch_allowed: HashSet::new(),
+        }
+    }
+
+    /// Records that we have requested a summary from `peer`.
+    pub(crate) fn request(&mut self, peer: PeerId) {
+        self.requested.insert(peer);
+    }
+
+    /// Returns whether a summary received from `peer` was requested.
+    pub(crate) fn summary_received(&mut self, peer: &PeerId) -> bool {
+        self.requested.remove(peer)
+    }
+
+    /// Records the transactions we are fetching from `peer`.
+    pub(crate) fn fetch(&mut self, peer: PeerId, tx_ids: Vec<TransactionId>) {
+        let now = Instant::now();
+        self.fetching.retain(|_, fetching| {
+            fetching.retain(|_, deadline| now < *deadline);
+            !fetching.is_empty()
+        });
+
+        let deadline = now + self.min_interval;
+        self.fetching
+            .entry(peer)
+            .or_default()
+            .extend(tx_ids.into_iter().map(|tx_id| (tx_id, deadline)));
+    }
+
+    /// Returns whether the transaction received from `peer` was fetched from it and is still
+    /// expected. The same transaction received from any other peer was routed to us.
+    pub(crate) fn transaction_received(&mut self, peer: &PeerId, tx_id: &TransactionId) -> bool {
+        let Some(fetching) = self.fetching.get_mut(peer) else {
+            return false;
+        };
+        let deadline = fetching.remove(tx_id);
+        if fetching.is_empty() {
+            self.fetching.remove(peer);
+        }
+        deadline.is_some_and(|deadline| Instant::now() < deadline)
+    }
+
+    /// Returns whether a summary can be served to `peer`, i.e. if the last one was served more than
+    /// the minimum interval ago.
+    pub(crate) fn serve_summary(&mut self, peer: PeerId) -> bool {
+        let now = Instant::now();
+        // Summaries served more than the minimum interval ago no longer limit anything.
+        self.served
+            .retain(|_, last| now < *last + self.min_interval);
+        if self
+            .served
+            .get(&peer)
+            .is_some_and(|last| now < *last + self.min_interval)
+        {
+            return false;
+        }
+        self.served.insert(peer, now);
+        self.fetch_allowed.insert(peer);
+        true
+    }
+
+    /// Returns whether `peer` can fetch transactions, i.e. if it has been served a summary since
+    /// its last fetch.
+    pub(crate) fn serve_fetch(&mut self, peer: &PeerId) -> bool {
+        self.fetch_allowed.remove(peer)
+    }
+
+    pub(crate) fn peer_disconnected(&mut self, peer: &PeerId) {
+        self.requested.remove(peer);
+        self.fetching.remove(peer);
+        self.fetch_allowed.remove(peer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fetched_transactions() {
+        let mut sync = SyncState::new(Duration::from_millis(50));
+        let (peer_a, peer_b) = (PeerId::random(), PeerId::random());
+        let (tx_a, tx_b) = (TransactionId::new(b"a"), TransactionId::new(b"b"));
+
+        sync.fetch(peer_a, vec![tx_a.clone(), tx_b.clone()]);
+
+        // Only the peer the transaction was fetched from replies to the sync.
+        assert!(!sync.transaction_received(&peer_b, &tx_a));
+        assert!(sync.transaction_received(&peer_a, &tx_a));
+        assert!(!sync.transaction_received(&peer_a, &tx_a));
+
+        // A transaction received after the deadline is no longer expected.
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(!sync.transaction_received(&peer_a, &tx_b));
+        assert!(sync.fetching.is_empty());
+    }
+
+    #[test]
+    fn expired_fetches_are_removed() {
+        let mut sync = SyncState::new(Duration::from_millis(50));
+        let (peer_a, peer_b) = (PeerId::random(), PeerId::random());
+
+        sync.fetch(peer_a, vec![TransactionId::new(b"a")]);
+        std::thread::sleep(Duration::from_millis(60));
+        sync.fetch(peer_b, vec![TransactionId::new(b"b")]);
+
+        assert!(!sync.fetching.contains_key(&peer_a));
+        assert!(sync.fetching.contains_key(&peer_b));
+    }
+
+    #[test]
+    fn served_summaries_are_pruned() {
+        let mut sync = SyncState::new(Duration::from_millis(50));
+        let (peer_a, peer_b) = (PeerId::random(), PeerId::random());
+
+        assert!(sync.serve_summary(peer_a));
+        assert!(!sync.serve_summary(peer_a));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(sync.serve_summary(peer_b));
+
+        assert!(!sync.served.contains_key(&peer_a));
+        assert!(sync.serve_summary(peer_a));
+    }
+}
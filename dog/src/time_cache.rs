@@ -2,6 +2,26 @@ use fnv::FnvHashMap;
 use std::{collections::VecDeque, time::Duration};
 use web_time::Instant;
 
+use crate::types::TransactionId;
+
+/// Estimates the memory used by a cache key outside of its inline size.
+pub(crate) trait HeapSize {
+    fn heap_size(&self) -> usize;
+}
+
+impl HeapSize for TransactionId {
+    fn heap_size(&self) -> usize {
+        self.0.len()
+    }
+}
+
+#[cfg(feature = "gossipsub")]
+impl HeapSize for libp2p::gossipsub::MessageId {
+    fn heap_size(&self) -> usize {
+        self.0.len()
+    }
+}
+
 struct ExpiringEntry<K> {
     key: K,
     expiration: Instant,
@@ -16,31 +36,69 @@ pub(crate) struct DuplicateCache<K, V> {
     list: VecDeque<ExpiringEntry<K>>,
     /// The time values remain in the cache.
     ttl: Duration,
+    /// The maximum number of values in the cache. When it is reached, the oldest value is evicted.
+    capacity: Option<usize>,
+    /// Estimate of the memory used by the entries of the cache, in bytes.
+    memory: usize,
+    /// Number of values expired since the last call to `take_removed`.
+    expired: usize,
+    /// Number of values evicted since the last call to `take_removed`.
+    evicted: usize,
 }
 
 impl<K, V> DuplicateCache<K, V>
 where
-    K: Eq + std::hash::Hash + Clone,
+    K: Eq + std::hash::Hash + Clone + HeapSize,
     V: Clone,
 {
-    pub(crate) fn new(ttl: Duration) -> Self {
+    pub(crate) fn new(ttl: Duration, capacity: Option<usize>) -> Self {
         DuplicateCache {
             len: 0,
             values: FnvHashMap::default(),
             list: VecDeque::new(),
             ttl,
+            capacity,
+            memory: 0,
+            expired: 0,
+            evicted: 0,
         }
     }
 
-    fn remove_expired_values(&mut self, now: Instant) {
-        while let Some(entry) = self.list.pop_front() {
-            if entry.expiration > now {
-                self.list.push_front(entry);
-                break;
-            }
-            self.len -= 1;
-            self.values.remove(&entry.key);
+    /// The estimated memory used by an entry: the key is stored both in the map and in the list.
+    fn entry_size(key: &K) -> usize {
+        std::mem::size_of::<K>()
+            + std::mem::size_of::<V>()
+            + std::mem::size_of::<ExpiringEntry<K>>()
+            + 2 * key.heap_size()
+    }
+
+    fn remove_front(&mut self) -> bool {
+        let Some(entry) = self.list.pop_front() else {
+            return false;
+        };
+        self.len -= 1;
+        self.memory -= Self::entry_size(&entry.key);
+        self.values.remove(&entry.key);
+        true
+    }
+
+    fn remove_expired_values(&mut self, now: Instant) -> usize {
+        let mut count = 0;
+        while self
+            .list
+            .front()
+            .is_some_and(|entry| entry.expiration <= now)
+        {
+            self.remove_front();
+            count += 1;
         }
+        self.expired += count;
+        count
+    }
+
+    /// Removes the expired values from the cache and returns their number.
+    pub(crate) fn remove_expired(&mut self) -> usize {
+        self.remove_expired_values(Instant::now())
     }
 
     pub(crate) fn insert(&mut self, key: K, value: V) -> bool {
@@ -48,6 +106,13 @@ where
         self.remove_expired_values(now);
 
         if !self.values.contains_key(&key) {
+            if let Some(capacity) = self.capacity {
+                while self.len >= capacity && self.remove_front() {
+                    self.evicted += 1;
+                }
+            }
+
+            self.memory += Self::entry_size(&key);
             self.values.insert(key.clone(), value);
             self.len += 1;
             self.list.push_back(ExpiringEntry {
@@ -71,4 +136,17 @@ where
     pub(crate) fn len(&self) -> usize {
         self.len
     }
+
+    /// An estimate of the memory used by the entries of the cache, in bytes.
+    pub(crate) fn memory(&self) -> usize {
+        self.memory
+    }
+
+    /// Returns the number of values expired and evicted since the last call, and resets them.
+    pub(crate) fn take_removed(&mut self) -> (usize, usize) {
+        (
+            std::mem::take(&mut self.expired),
+            std::mem::take(&mut self.evicted),
+        )
+    }
 }
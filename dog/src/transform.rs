@@ -1,4 +1,37 @@
-use crate::types::{RawTransaction, Transaction};
+use std::collections::BTreeMap;
+
+use libp2p::PeerId;
+
+use crate::types::{PublishOptions, RawTransaction, Transaction};
+
+/// The context of an outbound transform.
+#[derive(Debug, Clone)]
+pub struct OutboundContext {
+    /// The author of the transaction, if any.
+    pub author: Option<PeerId>,
+    /// The sequence number of the transaction, if any.
+    pub seqno: Option<u64>,
+    /// The peers the transaction is about to be sent to, empty if no peer is connected.
+    ///
+    /// NOTE: The transformed data is signed and sent as is to all of them, so a codec selected
+    /// per peer must be supported by every one of them.
+    pub peers: Vec<PeerId>,
+    /// The options given when publishing the transaction.
+    pub options: PublishOptions,
+}
+
+/// The context of an inbound transform.
+#[derive(Debug, Clone)]
+pub struct InboundContext {
+    /// The peer that forwarded us the transaction.
+    pub propagation_source: PeerId,
+    /// The author of the transaction, if any.
+    pub author: Option<PeerId>,
+    /// The sequence number of the transaction, if any.
+    pub seqno: Option<u64>,
+    /// The headers of the transaction, as given in [`PublishOptions::headers`] by its author.
+    pub headers: BTreeMap<String, Vec<u8>>,
+}
 
 /// A general trait of transforming a [`RawTransaction`] into a [`Transaction`] and vice versa.
 ///
@@ -19,6 +52,26 @@ pub trait DataTransform {
     /// Takes and transforms the data to be published. The transformed data will then be used
     /// to create a [`RawTransaction`] to be sent to peers.
     fn outbound_transform(&self, data: Vec<u8>) -> Result<Vec<u8>, std::io::Error>;
+
+    /// Takes a [`RawTransaction`] received along with its context and converts it to a
+    /// [`Transaction`]. Defaults to [`DataTransform::inbound_transform`].
+    fn inbound_transform_with_context(
+        &self,
+        raw_transaction: RawTransaction,
+        _context: &InboundContext,
+    ) -> Result<Transaction, std::io::Error> {
+        self.inbound_transform(raw_transaction)
+    }
+
+    /// Takes and transforms the data to be published along with its context. Defaults to
+    /// [`DataTransform::outbound_transform`].
+    fn outbound_transform_with_context(
+        &self,
+        data: Vec<u8>,
+        _context: &OutboundContext,
+    ) -> Result<Vec<u8>, std::io::Error> {
+        self.outbound_transform(data)
+    }
 }
 
 /// The default transform, the raw data is propagated as is to the application layer dog.
@@ -34,6 +87,7 @@ impl DataTransform for IdentityTransform {
             from: raw_transaction.from,
             seqno: raw_transaction.seqno,
             data: raw_transaction.data,
+            headers: raw_transaction.headers,
         })
     }
 
@@ -41,3 +95,623 @@ impl DataTransform for IdentityTransform {
         Ok(data)
     }
 }
+
+/// The default maximum size of decompressed transaction data.
+#[cfg(any(feature = "snappy", feature = "zstd"))]
+const DEFAULT_MAX_DECOMPRESSED_SIZE: usize = 1024 * 1024;
+
+/// A transform compressing transaction data with Snappy.
+///
+/// Inbound transactions whose data would decompress to more than the maximum size are rejected
+/// before being decompressed.
+#[cfg(feature = "snappy")]
+#[derive(Clone)]
+pub struct SnappyTransform {
+    max_decompressed_size: usize,
+}
+
+#[cfg(feature = "snappy")]
+impl SnappyTransform {
+    pub fn new(max_decompressed_size: usize) -> Self {
+        SnappyTransform {
+            max_decompressed_size,
+        }
+    }
+}
+
+#[cfg(feature = "snappy")]
+impl Default for SnappyTransform {
+    fn default() -> Self {
+        SnappyTransform::new(DEFAULT_MAX_DECOMPRESSED_SIZE)
+    }
+}
+
+#[cfg(feature = "snappy")]
+impl DataTransform for SnappyTransform {
+    fn inbound_transform(
+        &self,
+        raw_transaction: RawTransaction,
+    ) -> Result<Transaction, std::io::Error> {
+        let decompressed_size = snap::raw::decompress_len(&raw_transaction.data)?;
+        if decompressed_size > self.max_decompressed_size {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "decompressed size {} exceeds the maximum of {}",
+                    decompressed_size, self.max_decompressed_size
+                ),
+            ));
+        }
+
+        let data = snap::raw::Decoder::new().decompress_vec(&raw_transaction.data)?;
+
+        Ok(Transaction {
+            from: raw_transaction.from,
+            seqno: raw_transaction.seqno,
+            data,
+            headers: raw_transaction.headers,
+        })
+    }
+
+    fn outbound_transform(&self, data: Vec<u8>) -> Result<Vec<u8>, std::io::Error> {
+        Ok(snap::raw::Encoder::new().compress_vec(&data)?)
+    }
+}
+
+/// A transform compressing transaction data with Zstandard.
+///
+/// Inbound transactions whose data decompresses to more than the maximum size are rejected, the
+/// decompression stopping as soon as the maximum size is exceeded.
+#[cfg(feature = "zstd")]
+#[derive(Clone)]
+pub struct ZstdTransform {
+    level: i32,
+    max_decompressed_size: usize,
+}
+
+#[cfg(feature = "zstd")]
+impl ZstdTransform {
+    /// Creates a transform compressing with the given level, between 1 and 22. A level of 0
+    /// selects the default level of Zstandard.
+    pub fn new(level: i32, max_decompressed_size: usize) -> Self {
+        ZstdTransform {
+            level,
+            max_decompressed_size,
+        }
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl Default for ZstdTransform {
+    fn default() -> Self {
+        ZstdTransform::new(
+            zstd::DEFAULT_COMPRESSION_LEVEL,
+            DEFAULT_MAX_DECOMPRESSED_SIZE,
+        )
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl DataTransform for ZstdTransform {
+    fn inbound_transform(
+        &self,
+        raw_transaction: RawTransaction,
+    ) -> Result<Transaction, std::io::Error> {
+        use std::io::Read;
+
+        // The frame header may not contain the content size, and it cannot be trusted anyway, so
+        // the data is decompressed as a stream up to one byte more than the maximum size.
+        let mut data = Vec::new();
+        zstd::stream::read::Decoder::with_buffer(raw_transaction.data.as_slice())?
+            .take(self.max_decompressed_size as u64 + 1)
+            .read_to_end(&mut data)?;
+        if data.len() > self.max_decompressed_size {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "decompressed size exceeds the maximum of {}",
+                    self.max_decompressed_size
+                ),
+            ));
+        }
+
+        Ok(Transaction {
+            from: raw_transaction.from,
+            seqno: raw_transaction.seqno,
+            data,
+            headers: raw_transaction.headers,
+        })
+    }
+
+    fn outbound_transform(&self, data: Vec<u8>) -> Result<Vec<u8>, std::io::Error> {
+        zstd::bulk::compress(&data, self.level)
+    }
+}
+
+/// The size of the header of encrypted data: a 4-byte key id followed by a 12-byte nonce.
+#[cfg(feature = "encryption")]
+const ENCRYPTION_HEADER_SIZE: usize = 4 + 12;
+
+/// Returns the associated data authenticated along with encrypted data, binding the ciphertext to
+/// the key id, sequence number and author of the transaction so that it cannot be replayed in
+/// another transaction.
+#[cfg(feature = "encryption")]
+fn associated_data(key_id: u32, author: Option<&PeerId>, seqno: Option<u64>) -> Vec<u8> {
+    let mut aad = key_id.to_be_bytes().to_vec();
+    match seqno {
+        Some(seqno) => {
+            aad.push(1);
+            aad.extend_from_slice(&seqno.to_be_bytes());
+        }
+        None => aad.push(0),
+    }
+    if let Some(author) = author {
+        aad.extend_from_slice(&author.to_bytes());
+    }
+    aad
+}
+
+/// The group keys of an [`EncryptTransform`].
+#[cfg(feature = "encryption")]
+struct GroupKeys {
+    /// The keys that can be used to decrypt transactions, by key id.
+    ciphers: std::collections::HashMap<u32, chacha20poly1305::ChaCha20Poly1305>,
+    /// The id of the key used to encrypt transactions.
+    current: u32,
+}
+
+/// A transform encrypting transaction data with a symmetric group key, using ChaCha20-Poly1305,
+/// so that only the holders of the key can read it. Transactions are still routed on their
+/// encrypted bytes.
+///
+/// Each key has an id, sent along with the encrypted data, which allows rotating keys: a new key
+/// is first added on every member with [`EncryptTransform::add_key`], then used to encrypt with
+/// [`EncryptTransform::set_current_key`], and the previous key is eventually removed. Clones share
+/// the same keys, so a clone kept by the application can rotate the keys of the transform used by
+/// the behaviour.
+///
+/// The encrypted data is bound to the author and sequence number of the transaction, so it cannot
+/// be moved to another transaction. [`DataTransform::outbound_transform`], which has no context,
+/// encrypts data for a transaction without author nor sequence number.
+///
+/// NOTE: Transactions that cannot be decrypted, e.g. because the key is unknown, are considered
+/// invalid and are neither delivered nor forwarded.
+#[cfg(feature = "encryption")]
+#[derive(Clone)]
+pub struct EncryptTransform {
+    keys: std::sync::Arc<std::sync::RwLock<GroupKeys>>,
+}
+
+#[cfg(feature = "encryption")]
+impl EncryptTransform {
+    /// Creates a transform encrypting with the given key.
+    pub fn new(key_id: u32, key: [u8; 32]) -> Self {
+        use chacha20poly1305::KeyInit;
+
+        let mut ciphers = std::collections::HashMap::new();
+        ciphers.insert(
+            key_id,
+            chacha20poly1305::ChaCha20Poly1305::new(&chacha20poly1305::Key::from(key)),
+        );
+
+        EncryptTransform {
+            keys: std::sync::Arc::new(std::sync::RwLock::new(GroupKeys {
+                ciphers,
+                current: key_id,
+            })),
+        }
+    }
+
+    /// Adds a key that can be used to decrypt transactions, replacing any key with the same id.
+    pub fn add_key(&self, key_id: u32, key: [u8; 32]) {
+        use chacha20poly1305::KeyInit;
+
+        self.keys
+            .write()
+            .expect("Group keys lock poisoned")
+            .ciphers
+            .insert(
+                key_id,
+                chacha20poly1305::ChaCha20Poly1305::new(&chacha20poly1305::Key::from(key)),
+            );
+    }
+
+    /// Uses a previously added key to encrypt transactions. Returns `false` if the key is unknown.
+    pub fn set_current_key(&self, key_id: u32) -> bool {
+        let mut keys = self.keys.write().expect("Group keys lock poisoned");
+        if !keys.ciphers.contains_key(&key_id) {
+            return false;
+        }
+        keys.current = key_id;
+        true
+    }
+
+    /// Removes a key. The current key cannot be removed, in which case `false` is returned.
+    pub fn remove_key(&self, key_id: u32) -> bool {
+        let mut keys = self.keys.write().expect("Group keys lock poisoned");
+        if keys.current == key_id {
+            return false;
+        }
+        keys.ciphers.remove(&key_id).is_some()
+    }
+
+    /// Returns the id of the key used to encrypt transactions.
+    pub fn current_key_id(&self) -> u32 {
+        self.keys.read().expect("Group keys lock poisoned").current
+    }
+}
+
+#[cfg(feature = "encryption")]
+impl DataTransform for EncryptTransform {
+    fn inbound_transform(
+        &self,
+        raw_transaction: RawTransaction,
+    ) -> Result<Transaction, std::io::Error> {
+        use chacha20poly1305::aead::{Aead, Payload};
+
+        if raw_transaction.data.len() < ENCRYPTION_HEADER_SIZE {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "encrypted data too short",
+            ));
+        }
+
+        let (header, ciphertext) = raw_transaction.data.split_at(ENCRYPTION_HEADER_SIZE);
+        let (key_id, nonce) = header.split_at(4);
+
+        let keys = self.keys.read().expect("Group keys lock poisoned");
+        let key_id = u32::from_be_bytes(key_id.try_into().expect("4 bytes"));
+        let Some(cipher) = keys.ciphers.get(&key_id) else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("unknown key id {}", key_id),
+            ));
+        };
+
+        let data = cipher
+            .decrypt(
+                chacha20poly1305::Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: &associated_data(
+                        key_id,
+                        raw_transaction.from.as_ref(),
+                        raw_transaction.seqno,
+                    ),
+                },
+            )
+            .map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, "decryption failed")
+            })?;
+
+        Ok(Transaction {
+            from: raw_transaction.from,
+            seqno: raw_transaction.seqno,
+            data,
+            headers: raw_transaction.headers,
+        })
+    }
+
+    fn outbound_transform(&self, data: Vec<u8>) -> Result<Vec<u8>, std::io::Error> {
+        self.encrypt(data, None, None)
+    }
+
+    fn outbound_transform_with_context(
+        &self,
+        data: Vec<u8>,
+        context: &OutboundContext,
+    ) -> Result<Vec<u8>, std::io::Error> {
+        self.encrypt(data, context.author.as_ref(), context.seqno)
+    }
+}
+
+#[cfg(feature = "encryption")]
+impl EncryptTransform {
+    fn encrypt(
+        &self,
+        data: Vec<u8>,
+        author: Option<&PeerId>,
+        seqno: Option<u64>,
+    ) -> Result<Vec<u8>, std::io::Error> {
+        use chacha20poly1305::aead::{Aead, AeadCore, OsRng, Payload};
+
+        let keys = self.keys.read().expect("Group keys lock poisoned");
+        let cipher = keys.ciphers.get(&keys.current).ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "current key not found")
+        })?;
+
+        let nonce = chacha20poly1305::ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &data,
+                    aad: &associated_data(keys.current, author, seqno),
+                },
+            )
+            .map_err(|_| std::io::Error::other("encryption failed"))?;
+
+        let mut encrypted = Vec::with_capacity(ENCRYPTION_HEADER_SIZE + ciphertext.len());
+        encrypted.extend_from_slice(&keys.current.to_be_bytes());
+        encrypted.extend_from_slice(&nonce);
+        encrypted.extend_from_slice(&ciphertext);
+        Ok(encrypted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw_transaction(data: Vec<u8>) -> RawTransaction {
+        RawTransaction {
+            from: Some(PeerId::random()),
+            seqno: Some(1),
+            data,
+            headers: BTreeMap::new(),
+            priority: 0,
+            signature: None,
+            key: None,
+            hops: 0,
+            ttl: None,
+        }
+    }
+
+    /// A transform prefixing the data with the sequence number of the transaction, and reversing
+    /// it when the `reversed` header is set.
+    struct ContextTransform;
+
+    impl DataTransform for ContextTransform {
+        fn inbound_transform(
+            &self,
+            _raw_transaction: RawTransaction,
+        ) -> Result<Transaction, std::io::Error> {
+            Err(std::io::Error::other("context required"))
+        }
+
+        fn outbound_transform(&self, _data: Vec<u8>) -> Result<Vec<u8>, std::io::Error> {
+            Err(std::io::Error::other("context required"))
+        }
+
+        fn inbound_transform_with_context(
+            &self,
+            raw_transaction: RawTransaction,
+            context: &InboundContext,
+        ) -> Result<Transaction, std::io::Error> {
+            let mut data = raw_transaction.data;
+            if context.headers.contains_key("reversed") {
+                data.reverse();
+            }
+            let seqno = context.seqno.unwrap_or_default().to_be_bytes();
+            let Some(data) = data.strip_prefix(&seqno[..]) else {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "sequence number mismatch",
+                ));
+            };
+
+            Ok(Transaction {
+                from: context.author,
+                seqno: context.seqno,
+                data: data.to_vec(),
+                headers: context.headers.clone(),
+            })
+        }
+
+        fn outbound_transform_with_context(
+            &self,
+            data: Vec<u8>,
+            context: &OutboundContext,
+        ) -> Result<Vec<u8>, std::io::Error> {
+            let mut transformed = context.seqno.unwrap_or_default().to_be_bytes().to_vec();
+            transformed.extend_from_slice(&data);
+            if context.options.headers.contains_key("reversed") {
+                transformed.reverse();
+            }
+            Ok(transformed)
+        }
+    }
+
+    #[test]
+    fn context_transform() {
+        let headers = BTreeMap::from([("reversed".to_string(), Vec::new())]);
+        let raw_transaction = RawTransaction {
+            headers: headers.clone(),
+            ..raw_transaction(b"Hello!".to_vec())
+        };
+
+        let transformed = ContextTransform
+            .outbound_transform_with_context(
+                raw_transaction.data.clone(),
+                &OutboundContext {
+                    author: raw_transaction.from,
+                    seqno: raw_transaction.seqno,
+                    peers: Vec::new(),
+                    options: PublishOptions {
+                        headers,
+                        ..Default::default()
+                    },
+                },
+            )
+            .unwrap();
+        assert_ne!(transformed, raw_transaction.data);
+
+        let inbound_context = |raw_transaction: &RawTransaction| InboundContext {
+            propagation_source: PeerId::random(),
+            author: raw_transaction.from,
+            seqno: raw_transaction.seqno,
+            headers: raw_transaction.headers.clone(),
+        };
+
+        let received = RawTransaction {
+            data: transformed,
+            ..raw_transaction.clone()
+        };
+        let transaction = ContextTransform
+            .inbound_transform_with_context(received.clone(), &inbound_context(&received))
+            .unwrap();
+        assert_eq!(transaction.data, raw_transaction.data);
+        assert_eq!(transaction.from, raw_transaction.from);
+
+        let replayed = RawTransaction {
+            seqno: Some(2),
+            ..received
+        };
+        assert!(ContextTransform
+            .inbound_transform_with_context(replayed.clone(), &inbound_context(&replayed))
+            .is_err());
+    }
+
+    #[cfg(feature = "snappy")]
+    #[test]
+    fn snappy_round_trip() {
+        let transform = SnappyTransform::new(1024);
+        let data = b"Hello, Hello, Hello, Hello!".to_vec();
+
+        let compressed = transform.outbound_transform(data.clone()).unwrap();
+        let transaction = transform
+            .inbound_transform(raw_transaction(compressed))
+            .unwrap();
+
+        assert_eq!(transaction.data, data);
+    }
+
+    #[cfg(feature = "snappy")]
+    #[test]
+    fn snappy_rejects_over_limit() {
+        let compressed = SnappyTransform::new(4096)
+            .outbound_transform(vec![0; 2048])
+            .unwrap();
+
+        assert!(SnappyTransform::new(1024)
+            .inbound_transform(raw_transaction(compressed.clone()))
+            .is_err());
+        assert!(SnappyTransform::new(2048)
+            .inbound_transform(raw_transaction(compressed))
+            .is_ok());
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_round_trip() {
+        let transform = ZstdTransform::new(3, 1024);
+        let data = b"Hello, Hello, Hello, Hello!".to_vec();
+
+        let compressed = transform.outbound_transform(data.clone()).unwrap();
+        let transaction = transform
+            .inbound_transform(raw_transaction(compressed))
+            .unwrap();
+
+        assert_eq!(transaction.data, data);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_rejects_over_limit() {
+        let compressed = ZstdTransform::new(3, 1 << 20)
+            .outbound_transform(vec![0; 1 << 20])
+            .unwrap();
+
+        assert!(ZstdTransform::new(3, 1024)
+            .inbound_transform(raw_transaction(compressed.clone()))
+            .is_err());
+        assert!(ZstdTransform::new(3, 1 << 20)
+            .inbound_transform(raw_transaction(compressed))
+            .is_ok());
+    }
+
+    #[cfg(feature = "encryption")]
+    fn encrypt(transform: &EncryptTransform, raw_transaction: &RawTransaction) -> Vec<u8> {
+        transform
+            .outbound_transform_with_context(
+                raw_transaction.data.clone(),
+                &OutboundContext {
+                    author: raw_transaction.from,
+                    seqno: raw_transaction.seqno,
+                    peers: Vec::new(),
+                    options: PublishOptions::default(),
+                },
+            )
+            .unwrap()
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn encrypt_round_trip() {
+        let transform = EncryptTransform::new(1, [1; 32]);
+        let raw_transaction = raw_transaction(b"Hello!".to_vec());
+
+        let encrypted = RawTransaction {
+            data: encrypt(&transform, &raw_transaction),
+            ..raw_transaction.clone()
+        };
+        assert_ne!(encrypted.data, raw_transaction.data);
+
+        let transaction = transform.inbound_transform(encrypted.clone()).unwrap();
+        assert_eq!(transaction.data, raw_transaction.data);
+
+        // The encrypted data is bound to the author and sequence number.
+        assert!(transform
+            .inbound_transform(RawTransaction {
+                seqno: Some(2),
+                ..encrypted.clone()
+            })
+            .is_err());
+        assert!(transform
+            .inbound_transform(RawTransaction {
+                from: Some(PeerId::random()),
+                ..encrypted
+            })
+            .is_err());
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn encrypt_key_rotation() {
+        let transform = EncryptTransform::new(1, [1; 32]);
+        let raw_transaction = raw_transaction(b"Hello!".to_vec());
+        let encrypted = RawTransaction {
+            data: encrypt(&transform, &raw_transaction),
+            ..raw_transaction.clone()
+        };
+
+        transform.add_key(2, [2; 32]);
+        assert!(transform.set_current_key(2));
+        let rotated = RawTransaction {
+            data: encrypt(&transform, &raw_transaction),
+            ..raw_transaction.clone()
+        };
+
+        // Transactions encrypted with the previous key can still be decrypted.
+        assert_eq!(
+            transform.inbound_transform(encrypted.clone()).unwrap().data,
+            raw_transaction.data
+        );
+        assert_eq!(
+            transform.inbound_transform(rotated).unwrap().data,
+            raw_transaction.data
+        );
+
+        assert!(transform.remove_key(1));
+        assert!(transform.inbound_transform(encrypted).is_err());
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn encrypt_unknown_key() {
+        let raw_transaction = raw_transaction(b"Hello!".to_vec());
+        let encrypted = RawTransaction {
+            data: encrypt(&EncryptTransform::new(1, [1; 32]), &raw_transaction),
+            ..raw_transaction
+        };
+
+        let transform = EncryptTransform::new(2, [1; 32]);
+        assert!(transform.inbound_transform(encrypted.clone()).is_err());
+
+        // The key id is authenticated, so the data cannot be decrypted under another id of the
+        // same key.
+        let mut relabeled = encrypted;
+        relabeled.data[..4].copy_from_slice(&2u32.to_be_bytes());
+        assert!(transform.inbound_transform(relabeled).is_err());
+    }
+}
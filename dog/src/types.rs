@@ -1,3 +1,5 @@
+use std::{collections::BTreeMap, num::NonZeroU32};
+
 use futures_timer::Delay;
 use libp2p::{identity::ParseError, swarm::ConnectionId, PeerId};
 use quick_protobuf::MessageWrite;
@@ -32,23 +34,43 @@ impl std::fmt::Debug for TransactionId {
     }
 }
 
+/// Options given when publishing a transaction, passed to the outbound transform.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PublishOptions {
+    /// The headers of the transaction. They are signed with the transaction but not transformed,
+    /// and are passed to the inbound transform of the receivers, e.g. to select a codec.
+    pub headers: BTreeMap<String, Vec<u8>>,
+    /// The maximum number of hops the transaction may travel, unlimited if `None`. A time-to-live
+    /// of 1 only delivers the transaction to the peers of the local node.
+    pub ttl: Option<NonZeroU32>,
+    /// The priority class of the transaction, preserved when it is forwarded. See
+    /// [`crate::Config::priority_class_weights`].
+    pub priority: u32,
+}
+
 #[derive(Debug)]
 pub(crate) struct PeerConnections {
     /// The peer's connections.
     pub(crate) connections: Vec<ConnectionId>,
     /// The rpc sender to the connection handler(s).
     pub(crate) sender: Sender,
+    /// Whether the peer supports the DOG protocol on one of its connections.
+    pub(crate) protocol_supported: bool,
 }
 
 /// A transaction received by the dog system.
 #[derive(Debug, Clone)]
 pub struct RawTransaction {
-    /// The peer that published the transaction.
-    pub from: PeerId,
-    /// The sequence number of the transaction.
-    pub seqno: u64,
+    /// The peer that published the transaction, if any.
+    pub from: Option<PeerId>,
+    /// The sequence number of the transaction, if any.
+    pub seqno: Option<u64>,
     /// The content of the transaction.
     pub data: Vec<u8>,
+    /// The headers of the transaction, sorted by key.
+    pub headers: BTreeMap<String, Vec<u8>>,
+    /// The priority class of the transaction, see [`crate::Config::priority_class_weights`].
+    pub priority: u32,
 
     /// FIELDS BELOW ARE NOT PART OF THE SIGNATURE
 
@@ -56,6 +78,11 @@ pub struct RawTransaction {
     pub signature: Option<Vec<u8>>,
     /// The public key of the transaction if it is signed.
     pub key: Option<Vec<u8>>,
+    /// The number of times the transaction has been forwarded, 0 if it is sent by its author.
+    pub hops: u32,
+    /// The number of hops the transaction may still travel, if limited. A transaction is not
+    /// forwarded once it reaches zero.
+    pub ttl: Option<NonZeroU32>,
 }
 
 impl RawTransaction {
@@ -69,8 +96,11 @@ impl RawTransaction {
 impl From<RawTransaction> for proto::Transaction {
     fn from(tx: RawTransaction) -> Self {
         proto::Transaction {
-            from: tx.from.to_bytes(),
-            seqno: tx.seqno,
+            from: match tx.from {
+                Some(from) => from.to_bytes(),
+                None => vec![],
+            },
+            seqno: tx.seqno.unwrap_or_default(),
             data: tx.data.to_vec(),
             signature: match tx.signature {
                 Some(sig) => sig.to_vec(),
@@ -80,6 +110,14 @@ impl From<RawTransaction> for proto::Transaction {
                 Some(key) => key.to_vec(),
                 None => vec![],
             },
+            headers: tx
+                .headers
+                .into_iter()
+                .map(|(key, value)| proto::TransactionHeader { key, value })
+                .collect(),
+            priority: tx.priority,
+            hops: tx.hops,
+            ttl: tx.ttl.map(NonZeroU32::get).unwrap_or_default(),
         }
     }
 }
@@ -88,12 +126,14 @@ impl From<RawTransaction> for proto::Transaction {
 /// [`crate::transform::DataTransform`].
 #[derive(Clone)]
 pub struct Transaction {
-    /// The peer that published the transaction.
-    pub from: PeerId,
-    /// The sequence number of the transaction.
-    pub seqno: u64,
+    /// The peer that published the transaction, if any.
+    pub from: Option<PeerId>,
+    /// The sequence number of the transaction, if any.
+    pub seqno: Option<u64>,
     /// The content of the transaction.
     pub data: Vec<u8>,
+    /// The signed headers of the transaction, e.g. timestamps or routing hints.
+    pub headers: BTreeMap<String, Vec<u8>>,
 }
 
 impl std::fmt::Debug for Transaction {
@@ -101,13 +141,13 @@ impl std::fmt::Debug for Transaction {
         match String::from_utf8(self.data.clone()) {
             Ok(data) => write!(
                 f,
-                "Transaction {{ from: {}, seqno: {}, data: {} }}",
-                self.from, self.seqno, data
+                "Transaction {{ from: {:?}, seqno: {:?}, data: {}, headers: {:?} }}",
+                self.from, self.seqno, data, self.headers
             ),
             Err(_) => write!(
                 f,
-                "Transaction {{ from: {}, seqno: {}, data: {:?} }}",
-                self.from, self.seqno, self.data
+                "Transaction {{ from: {:?}, seqno: {:?}, data: {:?}, headers: {:?} }}",
+                self.from, self.seqno, self.data, self.headers
             ),
         }
     }
@@ -121,6 +161,13 @@ pub enum ControlAction {
     HaveTx(HaveTx),
     /// Node requests the local node to re-open a closed route to the requesting node.
     ResetRoute(ResetRoute),
+    /// Node requests a summary of the recent transactions of the local node, or some of these
+    /// transactions.
+    SyncRequest(SyncRequest),
+    /// Node sends a summary of its recent transactions in response to a sync request.
+    SyncSummary(SyncSummary),
+    /// Node sends a sketch of its recent transactions to reconcile them with the local node.
+    Reconcile(Reconcile),
 }
 
 #[derive(Debug, Clone)]
@@ -167,12 +214,111 @@ impl std::fmt::Display for ResetRoute {
     }
 }
 
+/// A request to catch up with the recent transactions of a peer. If `tx_ids` is empty, the peer
+/// responds with a [`SyncSummary`] of its recent transactions. Otherwise, it responds with the
+/// requested transactions.
+#[derive(Debug, Clone)]
+pub struct SyncRequest {
+    pub tx_ids: Vec<TransactionId>,
+}
+
+impl From<proto::ControlSyncRequest> for SyncRequest {
+    fn from(sync_request: proto::ControlSyncRequest) -> Self {
+        SyncRequest {
+            tx_ids: sync_request
+                .tx_ids
+                .into_iter()
+                .map(TransactionId::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<SyncRequest> for proto::ControlSyncRequest {
+    fn from(sync_request: SyncRequest) -> Self {
+        proto::ControlSyncRequest {
+            tx_ids: sync_request.tx_ids.into_iter().map(|id| id.0).collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for SyncRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SyncRequest {{ tx_ids: {} }}", self.tx_ids.len())
+    }
+}
+
+/// The ids of the recent transactions of a peer, sent in response to a [`SyncRequest`].
+#[derive(Debug, Clone)]
+pub struct SyncSummary {
+    pub tx_ids: Vec<TransactionId>,
+}
+
+impl From<proto::ControlSyncSummary> for SyncSummary {
+    fn from(sync_summary: proto::ControlSyncSummary) -> Self {
+        SyncSummary {
+            tx_ids: sync_summary
+                .tx_ids
+                .into_iter()
+                .map(TransactionId::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<SyncSummary> for proto::ControlSyncSummary {
+    fn from(sync_summary: SyncSummary) -> Self {
+        proto::ControlSyncSummary {
+            tx_ids: sync_summary.tx_ids.into_iter().map(|id| id.0).collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for SyncSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SyncSummary {{ tx_ids: {} }}", self.tx_ids.len())
+    }
+}
+
+/// A sketch of the recent transactions of a peer. The receiving node responds with a
+/// [`SyncSummary`] of the transactions it has that are missing from the sketch.
+#[derive(Debug, Clone)]
+pub struct Reconcile {
+    pub sketch: Vec<u8>,
+}
+
+impl From<proto::ControlReconcile> for Reconcile {
+    fn from(reconcile: proto::ControlReconcile) -> Self {
+        Reconcile {
+            sketch: reconcile.sketch,
+        }
+    }
+}
+
+impl From<Reconcile> for proto::ControlReconcile {
+    fn from(reconcile: Reconcile) -> Self {
+        proto::ControlReconcile {
+            sketch: reconcile.sketch,
+        }
+    }
+}
+
+impl std::fmt::Display for Reconcile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Reconcile {{ sketch: {} bytes }}", self.sketch.len())
+    }
+}
+
 /// A dog RPC transaction sent.
 #[derive(Debug)]
 pub enum RpcOut {
     /// Publish a dog transaction on the network. `timeout` limits the duration the transaction
     /// can wait to be sent before it is abandoned.
-    Publish { tx: RawTransaction, timeout: Delay },
+    Publish {
+        tx: RawTransaction,
+        tx_id: TransactionId,
+        timeout: Delay,
+    },
     /// Forward a dog transaction on the network. `timeout` limits the duration the transaction
     /// can wait to be sent before it is abandoned.
     Forward { tx: RawTransaction, timeout: Delay },
@@ -180,6 +326,12 @@ pub enum RpcOut {
     HaveTx(HaveTx),
     /// Send a ResetRoute control message.
     ResetRoute(ResetRoute),
+    /// Send a SyncRequest control message.
+    SyncRequest(SyncRequest),
+    /// Send a SyncSummary control message.
+    SyncSummary(SyncSummary),
+    /// Send a Reconcile control message.
+    Reconcile(Reconcile),
 }
 
 impl RpcOut {
@@ -191,7 +343,7 @@ impl RpcOut {
 impl From<RpcOut> for proto::RPC {
     fn from(rpc: RpcOut) -> Self {
         match rpc {
-            RpcOut::Publish { tx, timeout: _ } => proto::RPC {
+            RpcOut::Publish { tx, .. } => proto::RPC {
                 txs: vec![tx.into()],
                 control: None,
             },
@@ -204,6 +356,9 @@ impl From<RpcOut> for proto::RPC {
                 control: Some(proto::ControlMessage {
                     have_tx: vec![have_tx.into()],
                     reset_route: vec![],
+                    sync_request: vec![],
+                    sync_summary: vec![],
+                    reconcile: vec![],
                 }),
             },
             RpcOut::ResetRoute(reset_route) => proto::RPC {
@@ -211,6 +366,39 @@ impl From<RpcOut> for proto::RPC {
                 control: Some(proto::ControlMessage {
                     have_tx: vec![],
                     reset_route: vec![reset_route.into()],
+                    sync_request: vec![],
+                    sync_summary: vec![],
+                    reconcile: vec![],
+                }),
+            },
+            RpcOut::SyncRequest(sync_request) => proto::RPC {
+                txs: vec![],
+                control: Some(proto::ControlMessage {
+                    have_tx: vec![],
+                    reset_route: vec![],
+                    sync_request: vec![sync_request.into()],
+                    sync_summary: vec![],
+                    reconcile: vec![],
+                }),
+            },
+            RpcOut::SyncSummary(sync_summary) => proto::RPC {
+                txs: vec![],
+                control: Some(proto::ControlMessage {
+                    have_tx: vec![],
+                    reset_route: vec![],
+                    sync_request: vec![],
+                    sync_summary: vec![sync_summary.into()],
+                    reconcile: vec![],
+                }),
+            },
+            RpcOut::Reconcile(reconcile) => proto::RPC {
+                txs: vec![],
+                control: Some(proto::ControlMessage {
+                    have_tx: vec![],
+                    reset_route: vec![],
+                    sync_request: vec![],
+                    sync_summary: vec![],
+                    reconcile: vec![reconcile.into()],
                 }),
             },
         }
@@ -221,15 +409,32 @@ impl std::fmt::Display for RpcOut {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             RpcOut::Publish { tx, .. } => {
-                write!(f, "Publish {{ from: {}, seqno: {} }}", tx.from, tx.seqno)
+                write!(
+                    f,
+                    "Publish {{ from: {:?}, seqno: {:?} }}",
+                    tx.from, tx.seqno
+                )
             }
             RpcOut::Forward { tx, .. } => {
-                write!(f, "Forward {{ from: {}, seqno: {} }}", tx.from, tx.seqno)
+                write!(
+                    f,
+                    "Forward {{ from: {:?}, seqno: {:?} }}",
+                    tx.from, tx.seqno
+                )
             }
             RpcOut::HaveTx(have_tx) => write!(f, "HaveTx {{ have_tx: {} }}", have_tx),
             RpcOut::ResetRoute(reset_route) => {
                 write!(f, "ResetRoute {{ reset_route: {} }}", reset_route)
             }
+            RpcOut::SyncRequest(sync_request) => {
+                write!(f, "SyncRequest {{ sync_request: {} }}", sync_request)
+            }
+            RpcOut::SyncSummary(sync_summary) => {
+                write!(f, "SyncSummary {{ sync_summary: {} }}", sync_summary)
+            }
+            RpcOut::Reconcile(reconcile) => {
+                write!(f, "Reconcile {{ reconcile: {} }}", reconcile)
+            }
         }
     }
 }
@@ -272,6 +477,34 @@ impl From<Rpc> for proto::RPC {
                         _ => None,
                     })
                     .collect(),
+                sync_request: rpc
+                    .control_msgs
+                    .iter()
+                    .filter_map(|msg| match msg {
+                        ControlAction::SyncRequest(sync_request) => {
+                            Some((*sync_request).clone().into())
+                        }
+                        _ => None,
+                    })
+                    .collect(),
+                sync_summary: rpc
+                    .control_msgs
+                    .iter()
+                    .filter_map(|msg| match msg {
+                        ControlAction::SyncSummary(sync_summary) => {
+                            Some((*sync_summary).clone().into())
+                        }
+                        _ => None,
+                    })
+                    .collect(),
+                reconcile: rpc
+                    .control_msgs
+                    .iter()
+                    .filter_map(|msg| match msg {
+                        ControlAction::Reconcile(reconcile) => Some((*reconcile).clone().into()),
+                        _ => None,
+                    })
+                    .collect(),
             }),
         }
     }
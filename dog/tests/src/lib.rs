@@ -1,8 +1,20 @@
-use std::{str::FromStr, time::Duration};
+use std::{collections::VecDeque, convert::Infallible, str::FromStr, task::Poll, time::Duration};
 
 use libp2p::{
-    futures::StreamExt, identity::Keypair, swarm::dial_opts::DialOpts, Multiaddr, PeerId,
-    SwarmBuilder,
+    core::{
+        transport::PortUse,
+        upgrade::{OutboundUpgrade, ReadyUpgrade, UpgradeInfo},
+        Endpoint,
+    },
+    futures::{future::BoxFuture, AsyncWriteExt, FutureExt, StreamExt},
+    identity::Keypair,
+    swarm::{
+        dial_opts::DialOpts,
+        handler::{OneShotHandler, OneShotHandlerConfig},
+        ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, NotifyHandler,
+        SubstreamProtocol, THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+    },
+    Multiaddr, PeerId, Stream, StreamProtocol, SwarmBuilder,
 };
 use libp2p_dog::Route;
 use rand::Rng;
@@ -50,6 +62,22 @@ impl<const N: usize> Test<N> {
         configs: [libp2p_dog::Config; N],
         bootstrap_sets: [Vec<usize>; N],
         signed_transactions: bool,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::new_with_authenticity(
+            configs,
+            bootstrap_sets,
+            if signed_transactions {
+                Authenticity::Signed
+            } else {
+                Authenticity::Author
+            },
+        )
+    }
+
+    pub fn new_with_authenticity(
+        configs: [libp2p_dog::Config; N],
+        bootstrap_sets: [Vec<usize>; N],
+        authenticity: Authenticity,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let base_port = rand::thread_rng().gen_range(21000..50000);
 
@@ -65,7 +93,7 @@ impl<const N: usize> Test<N> {
                     })
                     .collect();
 
-                TestNode::new(addr, bootstrap_set, configs[i].clone(), signed_transactions).unwrap()
+                TestNode::new(addr, bootstrap_set, configs[i].clone(), authenticity).unwrap()
             })
             .collect::<Vec<_>>()
             .try_into()
@@ -98,6 +126,13 @@ impl<const N: usize> Test<N> {
         sleep(Duration::from_secs(5)).await;
     }
 
+    pub async fn spawn_node(&mut self, node: usize) {
+        assert!(node < N);
+        self.handlers[node] = Some(self.nodes[node].spawn());
+        // Wait for the swarm to initialize and dial its bootstrap set
+        sleep(Duration::from_secs(5)).await;
+    }
+
     pub async fn kill_node(&mut self, node: usize) {
         assert!(node < N);
         match &self.handlers[node] {
@@ -118,9 +153,38 @@ impl<const N: usize> Test<N> {
             .unwrap()
     }
 
+    pub fn addrs(&self) -> [Multiaddr; N] {
+        self.nodes
+            .iter()
+            .map(|node| node.addr())
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
+
     pub fn publish_on_node(&self, node: usize, data: Vec<u8>) {
+        self.publish_with_options_on_node(node, data, libp2p_dog::PublishOptions::default());
+    }
+
+    pub fn publish_with_options_on_node(
+        &self,
+        node: usize,
+        data: Vec<u8>,
+        options: libp2p_dog::PublishOptions,
+    ) {
+        assert!(node < N);
+        self.nodes[node].publish(data, options);
+    }
+
+    pub fn republish_on_node(&self, node: usize, tx_id: libp2p_dog::TransactionId) {
+        assert!(node < N);
+        self.nodes[node].republish(tx_id);
+    }
+
+    /// Returns a handle to publish transactions on a spawned node.
+    pub fn publisher(&self, node: usize) -> libp2p_dog::DogPublisher {
         assert!(node < N);
-        self.nodes[node].publish(data);
+        self.nodes[node].publisher()
     }
 
     pub fn collect_events(&mut self) -> [(Vec<libp2p_dog::Transaction>, Vec<Vec<Route>>); N] {
@@ -132,6 +196,18 @@ impl<const N: usize> Test<N> {
             .map_err(|_| "Failed to convert Vec to array")
             .unwrap()
     }
+
+    /// Returns the raw events of each node's behaviour along with the errors it reported, e.g.
+    /// failed publishes.
+    pub fn collect_dog_events(&mut self) -> [(Vec<libp2p_dog::Event>, Vec<String>); N] {
+        self.nodes
+            .iter_mut()
+            .map(|node| node.collect_dog_events())
+            .collect::<Vec<_>>()
+            .try_into()
+            .map_err(|_| "Failed to convert Vec to array")
+            .unwrap()
+    }
 }
 
 pub enum Event {
@@ -139,8 +215,23 @@ pub enum Event {
     Error(String),
 }
 
+/// How the transactions published by a test node are authenticated.
+#[derive(Debug, Clone, Copy)]
+pub enum Authenticity {
+    /// Signed with the node's keypair.
+    Signed,
+    /// Signed through a custom signer, published with a `DogPublisher`.
+    Custom,
+    /// Not signed, the node is the author.
+    Author,
+    /// Not signed, each transaction has a random author.
+    RandomAuthor,
+    /// Not signed, without author or sequence number.
+    Anonymous,
+}
+
 pub struct TestNode {
-    signed_transactions: bool,
+    authenticity: Authenticity,
     keypair: Keypair,
     peer_id: PeerId,
     addr: Multiaddr,
@@ -148,7 +239,9 @@ pub struct TestNode {
     config: libp2p_dog::Config,
     tx_event: mpsc::UnboundedSender<Event>,
     rx_event: mpsc::UnboundedReceiver<Event>,
-    tx_publish: Option<mpsc::UnboundedSender<Vec<u8>>>,
+    tx_publish: Option<mpsc::UnboundedSender<(Vec<u8>, libp2p_dog::PublishOptions)>>,
+    tx_republish: Option<mpsc::UnboundedSender<libp2p_dog::TransactionId>>,
+    publisher: Option<libp2p_dog::DogPublisher>,
 }
 
 impl TestNode {
@@ -156,7 +249,7 @@ impl TestNode {
         addr: Multiaddr,
         bootstrap_set: Vec<Multiaddr>,
         config: libp2p_dog::Config,
-        signed_transactions: bool,
+        authenticity: Authenticity,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let keypair = Keypair::generate_ed25519();
         let peer_id = PeerId::from_public_key(&keypair.public());
@@ -164,7 +257,7 @@ impl TestNode {
         let (tx_event, rx_event) = mpsc::unbounded_channel();
 
         Ok(Self {
-            signed_transactions,
+            authenticity,
             keypair,
             peer_id,
             addr,
@@ -173,6 +266,8 @@ impl TestNode {
             tx_event,
             rx_event,
             tx_publish: None,
+            tx_republish: None,
+            publisher: None,
         })
     }
 
@@ -180,54 +275,88 @@ impl TestNode {
         self.peer_id.clone()
     }
 
-    pub fn publish(&self, data: Vec<u8>) {
+    pub fn addr(&self) -> Multiaddr {
+        self.addr.clone()
+    }
+
+    pub fn publisher(&self) -> libp2p_dog::DogPublisher {
+        self.publisher.clone().expect("The node to be spawned")
+    }
+
+    pub fn publish(&self, data: Vec<u8>, options: libp2p_dog::PublishOptions) {
         if let Some(tx) = &self.tx_publish {
-            tx.send(data).unwrap_or_else(|e| {
+            tx.send((data, options)).unwrap_or_else(|e| {
                 println!("Failed to send publish message: {}", e);
             });
         }
     }
 
+    pub fn republish(&self, tx_id: libp2p_dog::TransactionId) {
+        if let Some(tx) = &self.tx_republish {
+            tx.send(tx_id).unwrap_or_else(|e| {
+                println!("Failed to send republish message: {}", e);
+            });
+        }
+    }
+
     pub fn spawn(&mut self) -> JoinHandle<()> {
-        let signed_transactions = self.signed_transactions;
+        let authenticity = self.authenticity;
         let keypair = self.keypair.clone();
         let addr = self.addr.clone();
         let bootstrap_set = self.bootstrap_set.clone();
         let config = self.config.clone();
         let tx_event = self.tx_event.clone();
-        let (tx_publish, mut rx_publish) = mpsc::unbounded_channel::<Vec<u8>>();
+        let (tx_publish, mut rx_publish) =
+            mpsc::unbounded_channel::<(Vec<u8>, libp2p_dog::PublishOptions)>();
 
-        self.tx_publish = Some(tx_publish);
+        let (tx_republish, mut rx_republish) =
+            mpsc::unbounded_channel::<libp2p_dog::TransactionId>();
 
-        tokio::spawn(async move {
-            let mut swarm: libp2p::Swarm<libp2p_dog::Behaviour> =
-                SwarmBuilder::with_existing_identity(keypair)
-                    .with_tokio()
-                    .with_tcp(
-                        libp2p::tcp::Config::new().nodelay(true), // Disable Nagle's algorithm
-                        libp2p::noise::Config::new,
-                        libp2p::yamux::Config::default,
-                    )
-                    .unwrap()
-                    .with_behaviour(|key| {
-                        libp2p_dog::Behaviour::new(
-                            if signed_transactions {
+        self.tx_publish = Some(tx_publish);
+        self.tx_republish = Some(tx_republish);
+
+        let mut swarm: libp2p::Swarm<libp2p_dog::Behaviour> =
+            SwarmBuilder::with_existing_identity(keypair)
+                .with_tokio()
+                .with_tcp(
+                    libp2p::tcp::Config::new().nodelay(true), // Disable Nagle's algorithm
+                    libp2p::noise::Config::new,
+                    libp2p::yamux::Config::default,
+                )
+                .unwrap()
+                .with_behaviour(|key| {
+                    libp2p_dog::Behaviour::new(
+                        match authenticity {
+                            Authenticity::Signed => {
                                 libp2p_dog::TransactionAuthenticity::Signed(key.clone())
-                            } else {
-                                libp2p_dog::TransactionAuthenticity::Author(
-                                    key.public().to_peer_id(),
-                                )
-                            },
-                            config,
-                        )
-                        .expect("Failed to create dog behaviour")
-                    })
-                    .unwrap()
-                    .with_swarm_config(|cfg| {
-                        cfg.with_idle_connection_timeout(std::time::Duration::from_secs(u64::MAX))
-                    })
-                    .build();
+                            }
+                            Authenticity::Custom => libp2p_dog::TransactionAuthenticity::Custom(
+                                Box::new(libp2p_dog::KeypairSigner::new(key.clone())),
+                            ),
+                            Authenticity::Author => libp2p_dog::TransactionAuthenticity::Author(
+                                key.public().to_peer_id(),
+                            ),
+                            Authenticity::RandomAuthor => {
+                                libp2p_dog::TransactionAuthenticity::RandomAuthor
+                            }
+                            Authenticity::Anonymous => {
+                                libp2p_dog::TransactionAuthenticity::Anonymous
+                            }
+                        },
+                        config,
+                    )
+                    .expect("Failed to create dog behaviour")
+                })
+                .unwrap()
+                .with_swarm_config(|cfg| {
+                    cfg.with_idle_connection_timeout(std::time::Duration::from_secs(u64::MAX))
+                })
+                .build();
+
+        let publisher = swarm.behaviour().publisher();
+        self.publisher = Some(publisher.clone());
 
+        tokio::spawn(async move {
             match swarm.listen_on(addr.clone()) {
                 Ok(_) => {}
                 Err(err) => {
@@ -277,8 +406,21 @@ impl TestNode {
                         }
                     }
 
-                    Some(data) = rx_publish.recv() => {
-                        match swarm.behaviour_mut().publish(data) {
+                    Some((data, options)) = rx_publish.recv() => {
+                        if let Authenticity::Custom = authenticity {
+                            // Custom signers are awaited outside of the swarm task.
+                            let publish = publisher.publish_with_options(data, options);
+                            let tx_event = tx_event.clone();
+                            tokio::spawn(async move {
+                                if let Err(err) = publish.await {
+                                    tx_event.send(Event::Error(format!("Failed to publish data: {}", err))).unwrap_or_else(|e| {
+                                        println!("Failed to send error message: {}", e);
+                                    });
+                                }
+                            });
+                            continue;
+                        }
+                        match swarm.behaviour_mut().publish_with_options(data, options) {
                             Ok(_) => {}
                             Err(err) => {
                                 tx_event.send(Event::Error(format!("Failed to publish data: {}", err))).unwrap_or_else(|e| {
@@ -288,6 +430,14 @@ impl TestNode {
                             }
                         }
                     }
+
+                    Some(tx_id) = rx_republish.recv() => {
+                        if let Err(err) = swarm.behaviour_mut().republish(&tx_id) {
+                            tx_event.send(Event::Error(format!("Failed to republish transaction: {}", err))).unwrap_or_else(|e| {
+                                println!("Failed to send error message: {}", e);
+                            });
+                        }
+                    }
                 }
             }
         })
@@ -305,6 +455,10 @@ impl TestNode {
                 Event::Dog(libp2p_dog::Event::RoutingUpdated { disabled_routes }) => {
                     routing_updates.push(disabled_routes);
                 }
+                Event::Dog(libp2p_dog::Event::PublishOutcome { .. }) => {}
+                Event::Dog(libp2p_dog::Event::PublishExpired { .. }) => {}
+                Event::Dog(libp2p_dog::Event::PeerRateLimited { .. }) => {}
+                Event::Dog(libp2p_dog::Event::PeerMisbehaviour { .. }) => {}
                 Event::Error(err) => {
                     panic!("Error: {}", err);
                 }
@@ -313,4 +467,450 @@ impl TestNode {
 
         (txns, routing_updates)
     }
+
+    pub fn collect_dog_events(&mut self) -> (Vec<libp2p_dog::Event>, Vec<String>) {
+        let mut events = Vec::new();
+        let mut errors = Vec::new();
+
+        while let Ok(event) = self.rx_event.try_recv() {
+            match event {
+                Event::Dog(event) => events.push(event),
+                Event::Error(err) => errors.push(err),
+            }
+        }
+
+        (events, errors)
+    }
+}
+
+#[derive(libp2p::swarm::NetworkBehaviour)]
+pub struct BridgeBehaviour {
+    dog: libp2p_dog::Behaviour,
+    gossipsub: libp2p::gossipsub::Behaviour,
+}
+
+/// A node running both DOG and gossipsub, bridged with a `GossipsubBridge`.
+pub struct BridgeNode {
+    peer_id: PeerId,
+    tx_publish: mpsc::UnboundedSender<Vec<u8>>,
+    rx_event: mpsc::UnboundedReceiver<BridgeBehaviourEvent>,
+    handle: JoinHandle<()>,
+}
+
+impl BridgeNode {
+    pub fn spawn(
+        addr: Multiaddr,
+        bootstrap_set: Vec<Multiaddr>,
+        config: libp2p_dog::Config,
+        topic: libp2p::gossipsub::IdentTopic,
+    ) -> Self {
+        let keypair = Keypair::generate_ed25519();
+        let peer_id = PeerId::from_public_key(&keypair.public());
+        let (tx_event, rx_event) = mpsc::unbounded_channel();
+        let (tx_publish, mut rx_publish) = mpsc::unbounded_channel::<Vec<u8>>();
+
+        let mut swarm = SwarmBuilder::with_existing_identity(keypair)
+            .with_tokio()
+            .with_tcp(
+                libp2p::tcp::Config::new().nodelay(true), // Disable Nagle's algorithm
+                libp2p::noise::Config::new,
+                libp2p::yamux::Config::default,
+            )
+            .unwrap()
+            .with_behaviour(|key| BridgeBehaviour {
+                dog: libp2p_dog::Behaviour::new(
+                    libp2p_dog::TransactionAuthenticity::Signed(key.clone()),
+                    config,
+                )
+                .expect("Failed to create dog behaviour"),
+                gossipsub: libp2p::gossipsub::Behaviour::new(
+                    libp2p::gossipsub::MessageAuthenticity::Signed(key.clone()),
+                    libp2p::gossipsub::Config::default(),
+                )
+                .expect("Failed to create gossipsub behaviour"),
+            })
+            .unwrap()
+            .with_swarm_config(|cfg| {
+                cfg.with_idle_connection_timeout(std::time::Duration::from_secs(u64::MAX))
+            })
+            .build();
+
+        let mut bridge = libp2p_dog::GossipsubBridge::new(topic.clone(), Duration::from_secs(30));
+
+        let handle = tokio::spawn(async move {
+            swarm
+                .behaviour_mut()
+                .gossipsub
+                .subscribe(&topic)
+                .expect("Failed to subscribe to the topic");
+            swarm.listen_on(addr).expect("Failed to listen on address");
+
+            // Wait for other swarm to start before dialing
+            sleep(Duration::from_secs(1)).await;
+
+            for node in bootstrap_set {
+                swarm.dial(node).expect("Failed to dial node");
+            }
+
+            loop {
+                tokio::select! {
+                    event = swarm.select_next_some() => {
+                        let libp2p::swarm::SwarmEvent::Behaviour(event) = event else {
+                            continue;
+                        };
+                        match &event {
+                            BridgeBehaviourEvent::Dog(event) => {
+                                if let Err(err) =
+                                    bridge.on_dog_event(event, &mut swarm.behaviour_mut().gossipsub)
+                                {
+                                    println!("Failed to bridge transaction: {}", err);
+                                }
+                            }
+                            BridgeBehaviourEvent::Gossipsub(event) => {
+                                if let Err(err) =
+                                    bridge.on_gossipsub_event(event, &mut swarm.behaviour_mut().dog)
+                                {
+                                    println!("Failed to bridge message: {}", err);
+                                }
+                            }
+                        }
+                        tx_event.send(event).unwrap_or_else(|e| {
+                            println!("Failed to send bridge event: {}", e);
+                        });
+                    }
+
+                    Some(data) = rx_publish.recv() => {
+                        if let Err(err) = swarm.behaviour_mut().dog.publish(data) {
+                            println!("Failed to publish data: {}", err);
+                        }
+                    }
+                }
+            }
+        });
+
+        Self {
+            peer_id,
+            tx_publish,
+            rx_event,
+            handle,
+        }
+    }
+
+    pub fn peer_id(&self) -> PeerId {
+        self.peer_id
+    }
+
+    /// Publishes a transaction on DOG.
+    pub fn publish(&self, data: Vec<u8>) {
+        self.tx_publish.send(data).unwrap_or_else(|e| {
+            println!("Failed to send publish message: {}", e);
+        });
+    }
+
+    /// Returns the transactions received through DOG and the messages received through gossipsub.
+    pub fn collect_events(
+        &mut self,
+    ) -> (
+        Vec<libp2p_dog::Transaction>,
+        Vec<libp2p::gossipsub::Message>,
+    ) {
+        let mut txns = Vec::new();
+        let mut messages = Vec::new();
+
+        while let Ok(event) = self.rx_event.try_recv() {
+            match event {
+                BridgeBehaviourEvent::Dog(libp2p_dog::Event::Transaction {
+                    transaction, ..
+                }) => {
+                    txns.push(transaction);
+                }
+                BridgeBehaviourEvent::Gossipsub(libp2p::gossipsub::Event::Message {
+                    message,
+                    ..
+                }) => {
+                    messages.push(message);
+                }
+                _ => {}
+            }
+        }
+
+        (txns, messages)
+    }
+}
+
+impl Drop for BridgeNode {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// A node that does not support the DOG protocol.
+pub struct DummyNode {
+    peer_id: PeerId,
+    handle: JoinHandle<()>,
+}
+
+impl DummyNode {
+    pub fn spawn(bootstrap_set: Vec<Multiaddr>) -> Self {
+        let keypair = Keypair::generate_ed25519();
+        let peer_id = PeerId::from_public_key(&keypair.public());
+
+        let mut swarm = SwarmBuilder::with_existing_identity(keypair)
+            .with_tokio()
+            .with_tcp(
+                libp2p::tcp::Config::new().nodelay(true), // Disable Nagle's algorithm
+                libp2p::noise::Config::new,
+                libp2p::yamux::Config::default,
+            )
+            .unwrap()
+            .with_behaviour(|_| libp2p::swarm::dummy::Behaviour)
+            .unwrap()
+            .with_swarm_config(|cfg| {
+                cfg.with_idle_connection_timeout(std::time::Duration::from_secs(u64::MAX))
+            })
+            .build();
+
+        let handle = tokio::spawn(async move {
+            for node in bootstrap_set {
+                swarm.dial(node).expect("Failed to dial node");
+            }
+
+            loop {
+                swarm.select_next_some().await;
+            }
+        });
+
+        Self { peer_id, handle }
+    }
+
+    pub fn peer_id(&self) -> PeerId {
+        self.peer_id
+    }
+}
+
+impl Drop for DummyNode {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+const DOG_PROTOCOL: StreamProtocol = StreamProtocol::new("/dog/1.0.0");
+
+/// Appends a protobuf length-delimited field to `buf`.
+fn encode_field(buf: &mut Vec<u8>, field: u8, value: &[u8]) {
+    buf.push(field << 3 | 2);
+    encode_varint(buf, value.len());
+    buf.extend_from_slice(value);
+}
+
+fn encode_varint(buf: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+/// Writes a single length-prefixed RPC on an outbound DOG substream.
+#[derive(Debug)]
+struct RawRpc(Vec<u8>);
+
+impl UpgradeInfo for RawRpc {
+    type Info = StreamProtocol;
+    type InfoIter = std::iter::Once<StreamProtocol>;
+
+    fn protocol_info(&self) -> Self::InfoIter {
+        std::iter::once(DOG_PROTOCOL)
+    }
+}
+
+impl OutboundUpgrade<Stream> for RawRpc {
+    type Output = ();
+    type Error = std::io::Error;
+    type Future = BoxFuture<'static, Result<(), std::io::Error>>;
+
+    fn upgrade_outbound(self, mut socket: Stream, _: Self::Info) -> Self::Future {
+        async move {
+            let mut frame = Vec::new();
+            encode_varint(&mut frame, self.0.len());
+            frame.extend_from_slice(&self.0);
+            socket.write_all(&frame).await?;
+            socket.close().await
+        }
+        .boxed()
+    }
+}
+
+#[derive(Debug)]
+enum RawHandlerEvent {
+    /// A substream opened by the peer, kept open so that its handler stays enabled.
+    Inbound(Stream),
+    Sent,
+}
+
+impl From<Stream> for RawHandlerEvent {
+    fn from(stream: Stream) -> Self {
+        RawHandlerEvent::Inbound(stream)
+    }
+}
+
+impl From<()> for RawHandlerEvent {
+    fn from(_: ()) -> Self {
+        RawHandlerEvent::Sent
+    }
+}
+
+type RawHandler = OneShotHandler<ReadyUpgrade<StreamProtocol>, RawRpc, RawHandlerEvent>;
+
+#[derive(Default)]
+struct RawBehaviour {
+    peers: Vec<PeerId>,
+    inbound_streams: Vec<Stream>,
+    events: VecDeque<ToSwarm<Infallible, RawRpc>>,
+}
+
+impl RawBehaviour {
+    fn handler() -> RawHandler {
+        OneShotHandler::new(
+            SubstreamProtocol::new(ReadyUpgrade::new(DOG_PROTOCOL), ()),
+            OneShotHandlerConfig::default(),
+        )
+    }
+
+    fn send(&mut self, rpc: Vec<u8>) {
+        for peer_id in &self.peers {
+            self.events.push_back(ToSwarm::NotifyHandler {
+                peer_id: *peer_id,
+                handler: NotifyHandler::Any,
+                event: RawRpc(rpc.clone()),
+            });
+        }
+    }
+}
+
+impl NetworkBehaviour for RawBehaviour {
+    type ConnectionHandler = RawHandler;
+    type ToSwarm = Infallible;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Self::handler())
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+        _: PortUse,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Self::handler())
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm) {
+        if let FromSwarm::ConnectionEstablished(established) = event {
+            self.peers.push(established.peer_id);
+        }
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        if let Ok(RawHandlerEvent::Inbound(stream)) = event {
+            self.inbound_streams.push(stream);
+        }
+    }
+
+    fn poll(
+        &mut self,
+        _: &mut std::task::Context<'_>,
+    ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        match self.events.pop_front() {
+            Some(event) => Poll::Ready(event),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// A node speaking the DOG protocol through hand-encoded messages, to send messages that honest
+/// nodes never send. It ignores the transactions it receives.
+pub struct RawNode {
+    peer_id: PeerId,
+    tx_rpc: mpsc::UnboundedSender<Vec<u8>>,
+    handle: JoinHandle<()>,
+}
+
+impl RawNode {
+    pub fn spawn(bootstrap_set: Vec<Multiaddr>) -> Self {
+        let keypair = Keypair::generate_ed25519();
+        let peer_id = PeerId::from_public_key(&keypair.public());
+
+        let mut swarm = SwarmBuilder::with_existing_identity(keypair)
+            .with_tokio()
+            .with_tcp(
+                libp2p::tcp::Config::new().nodelay(true), // Disable Nagle's algorithm
+                libp2p::noise::Config::new,
+                libp2p::yamux::Config::default,
+            )
+            .unwrap()
+            .with_behaviour(|_| RawBehaviour::default())
+            .unwrap()
+            .with_swarm_config(|cfg| {
+                cfg.with_idle_connection_timeout(std::time::Duration::from_secs(u64::MAX))
+            })
+            .build();
+
+        let (tx_rpc, mut rx_rpc) = mpsc::unbounded_channel();
+
+        let handle = tokio::spawn(async move {
+            for node in bootstrap_set {
+                swarm.dial(node).expect("Failed to dial node");
+            }
+
+            loop {
+                tokio::select! {
+                    _ = swarm.select_next_some() => {}
+                    Some(rpc) = rx_rpc.recv() => swarm.behaviour_mut().send(rpc),
+                }
+            }
+        });
+
+        Self {
+            peer_id,
+            tx_rpc,
+            handle,
+        }
+    }
+
+    pub fn peer_id(&self) -> PeerId {
+        self.peer_id
+    }
+
+    /// Sends a `HaveTx` message for the given transaction to the connected peers.
+    pub fn send_have_tx(&self, tx_id: &libp2p_dog::TransactionId) {
+        // RPC { control: ControlMessage { have_tx: [ControlHaveTx { tx_id }] } }
+        let mut have_tx = Vec::new();
+        encode_field(&mut have_tx, 1, &tx_id.0);
+        let mut control = Vec::new();
+        encode_field(&mut control, 1, &have_tx);
+        let mut rpc = Vec::new();
+        encode_field(&mut rpc, 2, &control);
+
+        self.tx_rpc.send(rpc).expect("Failed to send rpc");
+    }
+}
+
+impl Drop for RawNode {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
 }
@@ -1,7 +1,9 @@
-use std::time::Duration;
+use std::{collections::HashSet, str::FromStr, time::Duration};
 
-use libp2p_dog_tests::Test;
-use rand::seq::SliceRandom;
+use libp2p::Multiaddr;
+use libp2p_dog::GossipsubBridge;
+use libp2p_dog_tests::{Authenticity, BridgeNode, DummyNode, RawNode, Test};
+use rand::{seq::SliceRandom, Rng};
 use tokio::time::sleep;
 
 // Testing the dog behaviour with two nodes sending transactions to each other
@@ -42,9 +44,10 @@ pub async fn two_nodes_bidirectional() {
         let expected = (0..10)
             .map(|j| {
                 libp2p_dog::Transaction {
-                    from: peer_ids[1 - i],
-                    seqno: 0, // ignored
+                    from: Some(peer_ids[1 - i]),
+                    seqno: None, // ignored
                     data: format!("Hello #{} from node {}!", j, 2 - i).into_bytes(),
+                    headers: Default::default(),
                 }
             })
             .collect::<Vec<_>>();
@@ -106,9 +109,10 @@ pub async fn n_nodes_aligned() {
                 (0..N)
                     .filter(|k| *k != i)
                     .map(|k| libp2p_dog::Transaction {
-                        from: peer_ids[k],
-                        seqno: 0, // ignored
+                        from: Some(peer_ids[k]),
+                        seqno: None, // ignored
                         data: format!("Hello #{} from node {}!", j, k).into_bytes(),
+                        headers: Default::default(),
                     })
                     .collect::<Vec<_>>()
             })
@@ -172,9 +176,10 @@ pub async fn random_network() {
                 (0..N)
                     .filter(|k| *k != i)
                     .map(|k| libp2p_dog::Transaction {
-                        from: peer_ids[k],
-                        seqno: 0, // ignored
+                        from: Some(peer_ids[k]),
+                        seqno: None, // ignored
                         data: format!("Hello #{} from node {}!", j, k).into_bytes(),
+                        headers: Default::default(),
                     })
                     .collect::<Vec<_>>()
             })
@@ -286,9 +291,10 @@ pub async fn simple_reset_route_scenario() {
         assert_eq!(transactions.len(), B - R + 1);
         let mut expected = (0..B - R + 1)
             .map(|j| libp2p_dog::Transaction {
-                from: peer_ids[0],
-                seqno: 0, // ignored
+                from: Some(peer_ids[0]),
+                seqno: None, // ignored
                 data: format!("Hello #{} from node A!", j).into_bytes(),
+                headers: Default::default(),
             })
             .collect::<Vec<_>>();
 
@@ -375,9 +381,10 @@ pub async fn simple_reset_route_scenario() {
         assert_eq!(transactions.len(), R);
         let mut expected = (B - R - 1..B - 1)
             .map(|j| libp2p_dog::Transaction {
-                from: peer_ids[0],
-                seqno: 0, // ignored
+                from: Some(peer_ids[0]),
+                seqno: None, // ignored
                 data: format!("Hello #{} from node A!", j).into_bytes(),
+                headers: Default::default(),
             })
             .collect::<Vec<_>>();
 
@@ -413,3 +420,1784 @@ pub async fn simple_reset_route_scenario() {
         }
     }
 }
+
+// Testing that a node joining the network catches up with the recent transactions of its peer
+//     0 <---> 1 <---> 2 (joins later)
+#[tokio::test]
+pub async fn late_joiner_sync() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        // No redundancy to avoid the nodes sending reset route messages
+        .target_redundancy(0.0)
+        .redundancy_delta_percent(0)
+        .max_stored_transactions(100)
+        .sync_on_connection(true)
+        .build()
+        .unwrap();
+
+    let bootstrap_sets = [vec![], vec![0], vec![1]];
+
+    let mut test = match Test::<3>::new_with_unique_config(config, bootstrap_sets, true) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_node(0).await;
+    test.spawn_node(1).await;
+
+    for i in 0..10 {
+        test.publish_on_node(0, format!("Hello #{} from node 0!", i).into_bytes());
+    }
+
+    sleep(Duration::from_secs(2)).await;
+
+    test.spawn_node(2).await;
+
+    let peer_ids = test.peer_ids();
+    let events = test.collect_events();
+
+    assert_eq!(events[0].0.len(), 0);
+
+    let expected = (0..10)
+        .map(|i| format!("Hello #{} from node 0!", i).into_bytes())
+        .collect::<Vec<_>>();
+
+    for (transactions, routing_updates) in events.iter().skip(1) {
+        assert_eq!(transactions.len(), 10);
+
+        let mut data = transactions
+            .iter()
+            .map(|transaction| {
+                assert_eq!(transaction.from, Some(peer_ids[0]));
+                transaction.data.clone()
+            })
+            .collect::<Vec<_>>();
+        data.sort();
+
+        let mut expected = expected.clone();
+        expected.sort();
+        assert_eq!(data, expected);
+
+        assert_eq!(routing_updates.len(), 0);
+    }
+}
+
+// Testing that a node recovers the transactions it missed by periodically reconciling its
+// transaction store with its peer
+//     0 <---> 1 <---> 2 (joins later)
+#[tokio::test]
+pub async fn late_joiner_reconciliation() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        // No redundancy to avoid the nodes sending reset route messages
+        .target_redundancy(0.0)
+        .redundancy_delta_percent(0)
+        .max_stored_transactions(100)
+        .reconcile_interval(Duration::from_secs(2))
+        .sync_min_interval(Duration::from_secs(1))
+        .build()
+        .unwrap();
+
+    let bootstrap_sets = [vec![], vec![0], vec![1]];
+
+    let mut test = match Test::<3>::new_with_unique_config(config, bootstrap_sets, true) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_node(0).await;
+    test.spawn_node(1).await;
+
+    for i in 0..10 {
+        test.publish_on_node(0, format!("Hello #{} from node 0!", i).into_bytes());
+    }
+
+    sleep(Duration::from_secs(2)).await;
+
+    test.spawn_node(2).await;
+
+    let peer_ids = test.peer_ids();
+    let events = test.collect_events();
+
+    assert_eq!(events[0].0.len(), 0);
+
+    let mut expected = (0..10)
+        .map(|i| format!("Hello #{} from node 0!", i).into_bytes())
+        .collect::<Vec<_>>();
+    expected.sort();
+
+    for (transactions, _) in events.iter().skip(1) {
+        assert_eq!(transactions.len(), 10);
+
+        let mut data = transactions
+            .iter()
+            .map(|transaction| {
+                assert_eq!(transaction.from, Some(peer_ids[0]));
+                transaction.data.clone()
+            })
+            .collect::<Vec<_>>();
+        data.sort();
+        assert_eq!(data, expected);
+    }
+}
+
+// Testing that a node ignores the reconciliation sketches larger than its own
+//     0 <---> 1 <---> 2 (joins later, larger sketches)
+#[tokio::test]
+pub async fn reconciliation_sketch_too_large() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        // No redundancy to avoid the nodes sending reset route messages
+        .target_redundancy(0.0)
+        .redundancy_delta_percent(0)
+        .max_stored_transactions(100)
+        .reconcile_interval(Duration::from_secs(2))
+        .sync_min_interval(Duration::from_secs(1))
+        .build()
+        .unwrap();
+    let large_config = libp2p_dog::ConfigBuilder::from(config.clone())
+        .reconcile_sketch_size(960)
+        .build()
+        .unwrap();
+
+    let bootstrap_sets = [vec![], vec![0], vec![1]];
+
+    let mut test = match Test::<3>::new_with_each_config(
+        [config.clone(), config, large_config],
+        bootstrap_sets,
+        true,
+    ) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_node(0).await;
+    test.spawn_node(1).await;
+
+    for i in 0..10 {
+        test.publish_on_node(0, format!("Hello #{} from node 0!", i).into_bytes());
+    }
+
+    sleep(Duration::from_secs(2)).await;
+
+    test.spawn_node(2).await;
+
+    let events = test.collect_events();
+
+    assert_eq!(events[1].0.len(), 10);
+    assert_eq!(events[2].0.len(), 0);
+}
+
+// Testing that a node drops the transactions of a peer exceeding its rate limit
+//     0 ---> 1 (rate limited)
+#[tokio::test]
+pub async fn rate_limited_peer() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        // No redundancy to avoid the nodes sending reset route messages
+        .target_redundancy(0.0)
+        .redundancy_delta_percent(0)
+        .build()
+        .unwrap();
+    let limited_config = libp2p_dog::ConfigBuilder::from(config.clone())
+        .peer_max_transactions_per_second(5.0)
+        .build()
+        .unwrap();
+
+    let bootstrap_sets = [vec![1], vec![0]];
+
+    let mut test =
+        match Test::<2>::new_with_each_config([config, limited_config], bootstrap_sets, true) {
+            Ok(test) => test,
+            Err(e) => panic!("Failed to create test: {}", e),
+        };
+
+    test.spawn_all().await;
+
+    for i in 0..50 {
+        test.publish_on_node(0, format!("Hello #{} from node 0!", i).into_bytes());
+    }
+
+    sleep(Duration::from_secs(2)).await;
+
+    let events = test.collect_events();
+
+    let received = events[1].0.len();
+    assert!(received >= 5, "received {} transactions", received);
+    assert!(received < 50, "received {} transactions", received);
+}
+
+// Testing that no route is disabled when it would leave a peer with less than the minimum number
+// of active routes, in a line where each node has two routes to each of its neighbours
+//     0 <---> 1 <---> 2 <---> ... <---> n-1
+#[tokio::test]
+pub async fn min_active_routes_line() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        // We force the nodes to remove any redundancy
+        .target_redundancy(0.0)
+        .redundancy_delta_percent(0)
+        .min_active_routes_per_peer(2)
+        // Disable signature to speed up the test
+        .validation_mode(libp2p_dog::ValidationMode::None)
+        .build()
+        .unwrap();
+
+    const N: usize = 5;
+
+    let bootstrap_sets: [Vec<usize>; N] = (0..N)
+        .map(|i| if i == 0 { vec![] } else { vec![i - 1] })
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap();
+
+    let mut test = match Test::<N>::new_with_unique_config(config, bootstrap_sets, false) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    for i in 0..10 {
+        for j in 0..N {
+            test.publish_on_node(j, format!("Hello #{} from node {}!", i, j).into_bytes());
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    sleep(Duration::from_secs(5)).await;
+
+    let events = test.collect_events();
+
+    for (transactions, routing_updates) in events.iter() {
+        assert_eq!(transactions.len(), (N - 1) * 10);
+        assert!(routing_updates.iter().all(|routes| routes.is_empty()));
+    }
+}
+
+// Testing that a relay does not disable its last route to a node other than its own one, even if
+// the node sends have_tx messages
+// We consider the following network:
+//     A <---> Bi <---> C
+// where A is the transaction sender, Bi (1 <= i <= B) are relays forming a star around C. Each Bi
+// has two routes to C: from A and from itself.
+#[tokio::test]
+pub async fn min_active_routes_star() {
+    const B: usize = 4; // Number of Bi nodes
+    const N: usize = B + 2; // Total number of nodes
+
+    let config_a_bi = libp2p_dog::ConfigBuilder::default()
+        // We force the nodes to remove any redundancy
+        .target_redundancy(0.0)
+        .redundancy_delta_percent(0)
+        .min_active_routes_per_peer(2)
+        // Disable signature to speed up the test
+        .validation_mode(libp2p_dog::ValidationMode::None)
+        .build()
+        .unwrap();
+    let config_c = libp2p_dog::ConfigBuilder::from(config_a_bi.clone())
+        // Speed up have_tx unblocking
+        .redundancy_interval(Duration::from_millis(10))
+        // For simplicity, node C acts as a client
+        .forward_transactions(false)
+        .build()
+        .unwrap();
+
+    let mut configs = vec![config_a_bi; B + 1];
+    configs.push(config_c);
+
+    let mut bootstrap_sets: Vec<Vec<usize>> = Vec::with_capacity(N);
+    bootstrap_sets.push((1..B + 1).collect());
+    for _ in 0..B {
+        bootstrap_sets.push(vec![N - 1]);
+    }
+    bootstrap_sets.push(vec![]);
+
+    let mut test = match Test::<N>::new_with_each_config(
+        match configs.try_into() {
+            Ok(configs_array) => configs_array,
+            Err(_) => panic!("Failed to convert Vec to array"),
+        },
+        bootstrap_sets
+            .try_into()
+            .expect("Failed to convert Vec to array"),
+        false,
+    ) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    for i in 0..10 {
+        test.publish_on_node(0, format!("Hello #{} from node A!", i).into_bytes());
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    sleep(Duration::from_secs(5)).await;
+
+    let events = test.collect_events();
+
+    for (i, (transactions, routing_updates)) in events.iter().enumerate() {
+        if i != 0 {
+            assert_eq!(transactions.len(), 10);
+        }
+        assert!(routing_updates.iter().all(|routes| routes.is_empty()));
+    }
+}
+
+// Testing that nodes using content-addressed transaction ids and fast transaction ids deliver each
+// transaction once, although they receive duplicates
+//     0 <---> 1 <---> 2 <---> 0
+#[tokio::test]
+pub async fn content_addressed_ids() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        .transaction_id_mode(libp2p_dog::TransactionIdMode::Sha256)
+        // The data is not transformed, so the fast id is the transaction id
+        .fast_transaction_id_fn(|raw_transaction| {
+            libp2p_dog::TransactionIdMode::Sha256.transaction_id(&libp2p_dog::Transaction {
+                from: raw_transaction.from,
+                seqno: raw_transaction.seqno,
+                data: raw_transaction.data.clone(),
+                headers: raw_transaction.headers.clone(),
+            })
+        })
+        // Disable signature to speed up the test
+        .validation_mode(libp2p_dog::ValidationMode::None)
+        .build()
+        .unwrap();
+
+    const N: usize = 3;
+
+    let bootstrap_sets = [vec![1, 2], vec![2], vec![]];
+
+    let mut test = match Test::<N>::new_with_unique_config(config, bootstrap_sets, false) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    for i in 0..10 {
+        for j in 0..N {
+            test.publish_on_node(j, format!("Hello #{} from node {}!", i, j).into_bytes());
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    sleep(Duration::from_secs(5)).await;
+
+    let events = test.collect_events();
+
+    for (i, (transactions, _)) in events.iter().enumerate() {
+        let mut data = transactions
+            .iter()
+            .map(|transaction| transaction.data.clone())
+            .collect::<Vec<_>>();
+        data.sort();
+
+        let mut expected = (0..10)
+            .flat_map(|j| {
+                (0..N)
+                    .filter(move |k| *k != i)
+                    .map(move |k| format!("Hello #{} from node {}!", j, k).into_bytes())
+            })
+            .collect::<Vec<_>>();
+        expected.sort();
+
+        assert_eq!(data, expected);
+    }
+}
+
+// Testing that the signed headers of a transaction are delivered unchanged through a relay
+//     A ---> B ---> C
+#[tokio::test]
+pub async fn signed_headers() {
+    let config = libp2p_dog::ConfigBuilder::default().build().unwrap();
+
+    let bootstrap_sets = [vec![1], vec![2], vec![]];
+
+    let mut test = match Test::<3>::new_with_unique_config(config, bootstrap_sets, true) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    let headers = |i: u64| {
+        [
+            ("index".to_string(), i.to_be_bytes().to_vec()),
+            ("origin".to_string(), b"A".to_vec()),
+        ]
+        .into_iter()
+        .collect::<std::collections::BTreeMap<_, _>>()
+    };
+
+    for i in 0..10 {
+        test.publish_with_options_on_node(
+            0,
+            format!("Hello #{} from node A!", i).into_bytes(),
+            libp2p_dog::PublishOptions {
+                headers: headers(i),
+                ..Default::default()
+            },
+        );
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    sleep(Duration::from_secs(5)).await;
+
+    let events = test.collect_events();
+
+    for (transactions, _) in events.iter().skip(1) {
+        assert_eq!(transactions.len(), 10);
+
+        for transaction in transactions {
+            let index = (0..10)
+                .find(|i| transaction.data == format!("Hello #{} from node A!", i).into_bytes())
+                .expect("Unexpected transaction");
+            assert_eq!(transaction.headers, headers(index));
+        }
+    }
+}
+
+// Testing that transactions are not forwarded beyond their time-to-live
+//     A ---> B ---> C ---> D
+#[tokio::test]
+pub async fn transaction_ttl() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        // Disable signature to speed up the test
+        .validation_mode(libp2p_dog::ValidationMode::None)
+        .build()
+        .unwrap();
+
+    const N: usize = 4;
+
+    let bootstrap_sets: [Vec<usize>; N] = (0..N)
+        .map(|i| if i == N - 1 { vec![] } else { vec![i + 1] })
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap();
+
+    let mut test = match Test::<N>::new_with_unique_config(config, bootstrap_sets, false) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    // Even transactions may only travel two hops, odd transactions are not limited
+    for i in 0..10 {
+        test.publish_with_options_on_node(
+            0,
+            format!("Hello #{} from node A!", i).into_bytes(),
+            libp2p_dog::PublishOptions {
+                ttl: if i % 2 == 0 {
+                    std::num::NonZeroU32::new(2)
+                } else {
+                    None
+                },
+                ..Default::default()
+            },
+        );
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    sleep(Duration::from_secs(5)).await;
+
+    let events = test.collect_events();
+
+    for (i, (transactions, _)) in events.iter().enumerate().skip(1) {
+        let mut data = transactions
+            .iter()
+            .map(|transaction| transaction.data.clone())
+            .collect::<Vec<_>>();
+        data.sort();
+
+        let mut expected = (0..10)
+            .filter(|j| i <= 2 || j % 2 == 1)
+            .map(|j| format!("Hello #{} from node A!", j).into_bytes())
+            .collect::<Vec<_>>();
+        expected.sort();
+
+        assert_eq!(data, expected);
+    }
+}
+
+// Testing that transactions of every priority class are delivered through a relay
+//     A ---> B ---> C
+#[tokio::test]
+pub async fn priority_classes() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        .priority_class_weights(vec![4, 1])
+        .build()
+        .unwrap();
+
+    let bootstrap_sets = [vec![1], vec![2], vec![]];
+
+    let mut test = match Test::<3>::new_with_unique_config(config, bootstrap_sets, true) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    // The last class is unknown and uses the lowest weight
+    let priorities = [0, 1, 7];
+
+    for i in 0..10 {
+        for priority in priorities {
+            test.publish_with_options_on_node(
+                0,
+                format!("Hello #{} of class {} from node A!", i, priority).into_bytes(),
+                libp2p_dog::PublishOptions {
+                    priority,
+                    ..Default::default()
+                },
+            );
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    sleep(Duration::from_secs(5)).await;
+
+    let events = test.collect_events();
+
+    for (transactions, _) in events.iter().skip(1) {
+        let mut data = transactions
+            .iter()
+            .map(|transaction| transaction.data.clone())
+            .collect::<Vec<_>>();
+        data.sort();
+
+        let mut expected = (0..10)
+            .flat_map(|i| {
+                priorities.map(|priority| {
+                    format!("Hello #{} of class {} from node A!", i, priority).into_bytes()
+                })
+            })
+            .collect::<Vec<_>>();
+        expected.sort();
+
+        assert_eq!(data, expected);
+    }
+}
+
+// Testing the publication of transactions signed by a custom signer
+//     A <---> B <---> C <---> A
+#[tokio::test]
+pub async fn custom_signer() {
+    let config = libp2p_dog::ConfigBuilder::default().build().unwrap();
+
+    const N: usize = 3;
+
+    let bootstrap_sets = [vec![1, 2], vec![2], vec![]];
+
+    let mut test = match Test::<N>::new_with_authenticity(
+        std::array::from_fn(|_| config.clone()),
+        bootstrap_sets,
+        Authenticity::Custom,
+    ) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    for i in 0..10 {
+        for j in 0..N {
+            test.publish_on_node(j, format!("Hello #{} from node {}!", i, j).into_bytes());
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    sleep(Duration::from_secs(5)).await;
+
+    let peer_ids = test.peer_ids();
+    let events = test.collect_events();
+
+    for (i, (transactions, _)) in events.iter().enumerate() {
+        let mut received = transactions
+            .iter()
+            .map(|transaction| (transaction.from, transaction.data.clone()))
+            .collect::<Vec<_>>();
+        received.sort();
+
+        let mut expected = (0..10)
+            .flat_map(|j| {
+                (0..N).filter(move |k| *k != i).map(move |k| {
+                    (
+                        Some(peer_ids[k]),
+                        format!("Hello #{} from node {}!", j, k).into_bytes(),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        expected.sort();
+
+        assert_eq!(received, expected);
+    }
+}
+
+// Testing the publication of anonymous transactions, which only peers validating in the
+// anonymous mode accept
+//     B <---> A <---> C
+#[tokio::test]
+pub async fn anonymous_authenticity() {
+    let config = |validation_mode| {
+        libp2p_dog::ConfigBuilder::default()
+            .validation_mode(validation_mode)
+            .transaction_id_mode(libp2p_dog::TransactionIdMode::Sha256)
+            .build()
+            .unwrap()
+    };
+
+    const N: usize = 3;
+
+    let bootstrap_sets = [vec![1, 2], vec![], vec![]];
+
+    let mut test = match Test::<N>::new_with_authenticity(
+        [
+            config(libp2p_dog::ValidationMode::Anonymous),
+            config(libp2p_dog::ValidationMode::Anonymous),
+            config(libp2p_dog::ValidationMode::Strict),
+        ],
+        bootstrap_sets,
+        Authenticity::Anonymous,
+    ) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    for i in 0..10 {
+        test.publish_on_node(0, format!("Hello #{} from node A!", i).into_bytes());
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    sleep(Duration::from_secs(5)).await;
+
+    let events = test.collect_events();
+
+    // The anonymous validation mode rejects any transaction with an author, a sequence number or
+    // a signature.
+    let mut received = events[1]
+        .0
+        .iter()
+        .map(|transaction| {
+            assert_eq!(transaction.from, None);
+            assert_eq!(transaction.seqno, None);
+            transaction.data.clone()
+        })
+        .collect::<Vec<_>>();
+    received.sort();
+
+    let mut expected = (0..10)
+        .map(|i| format!("Hello #{} from node A!", i).into_bytes())
+        .collect::<Vec<_>>();
+    expected.sort();
+
+    assert_eq!(received, expected);
+
+    // The strict validation mode rejects transactions without an author.
+    assert!(events[2].0.is_empty());
+}
+
+// Testing the publication of transactions with a random author
+//     A <---> B <---> C <---> A
+#[tokio::test]
+pub async fn random_author_authenticity() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        .validation_mode(libp2p_dog::ValidationMode::Author)
+        .build()
+        .unwrap();
+
+    const N: usize = 3;
+
+    let bootstrap_sets = [vec![1, 2], vec![2], vec![]];
+
+    let mut test = match Test::<N>::new_with_authenticity(
+        std::array::from_fn(|_| config.clone()),
+        bootstrap_sets,
+        Authenticity::RandomAuthor,
+    ) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    for i in 0..10 {
+        test.publish_on_node(0, format!("Hello #{} from node A!", i).into_bytes());
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    sleep(Duration::from_secs(5)).await;
+
+    let peer_ids = test.peer_ids();
+    let events = test.collect_events();
+
+    for (transactions, _) in events.iter().skip(1) {
+        let mut received = transactions
+            .iter()
+            .map(|transaction| transaction.data.clone())
+            .collect::<Vec<_>>();
+        received.sort();
+
+        let mut expected = (0..10)
+            .map(|i| format!("Hello #{} from node A!", i).into_bytes())
+            .collect::<Vec<_>>();
+        expected.sort();
+
+        assert_eq!(received, expected);
+
+        let authors = transactions
+            .iter()
+            .map(|transaction| {
+                assert!(transaction.seqno.is_some());
+                transaction.from.expect("The author should be present")
+            })
+            .collect::<HashSet<_>>();
+
+        // Each transaction has its own author, none of them being the publisher.
+        assert_eq!(authors.len(), 10);
+        assert!(!authors.contains(&peer_ids[0]));
+    }
+}
+
+#[tokio::test]
+pub async fn publisher_ordering() {
+    let config = libp2p_dog::ConfigBuilder::default().build().unwrap();
+
+    const N: usize = 3;
+
+    let bootstrap_sets = [vec![1, 2], vec![2], vec![]];
+
+    let mut test = match Test::<N>::new_with_each_config(
+        std::array::from_fn(|_| config.clone()),
+        bootstrap_sets,
+        true,
+    ) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    let publisher = test.publisher(0);
+    let pending = (0..20)
+        .map(|i| publisher.publish(format!("Hello #{} from node 0!", i).into_bytes()))
+        .collect::<Vec<_>>();
+
+    let mut transaction_ids = HashSet::new();
+    for publish in pending {
+        match publish.await {
+            Ok(transaction_id) => transaction_ids.insert(transaction_id),
+            Err(e) => panic!("Failed to publish: {}", e),
+        };
+    }
+    assert_eq!(transaction_ids.len(), 20);
+
+    sleep(Duration::from_secs(2)).await;
+
+    let peer_ids = test.peer_ids();
+    let events = test.collect_events();
+
+    for (transactions, _) in events.iter().skip(1) {
+        let mut received = transactions
+            .iter()
+            .filter(|transaction| transaction.from == Some(peer_ids[0]))
+            .map(|transaction| (transaction.seqno, transaction.data.clone()))
+            .collect::<Vec<_>>();
+        received.sort();
+
+        // Sequence numbers follow the order of the requests.
+        let expected = (0..20)
+            .map(|i| format!("Hello #{} from node 0!", i).into_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            received
+                .into_iter()
+                .map(|(_, data)| data)
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+}
+
+// Testing that publish requests exceeding the publish request queue are all handled on an idle
+// swarm, where the transactions are buffered as no peer is connected
+//     0
+#[tokio::test]
+pub async fn publisher_queue_full() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        // Keep the swarm idle
+        .redundancy_interval(Duration::from_secs(100))
+        .publish_request_queue_len(2)
+        .max_pending_publishes(20)
+        .build()
+        .unwrap();
+
+    let mut test = match Test::<1>::new_with_unique_config(config, [vec![]], true) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    let publisher = test.publisher(0);
+    let pending = (0..20)
+        .map(|i| publisher.publish(format!("Hello #{} from node 0!", i).into_bytes()))
+        .collect::<Vec<_>>();
+
+    for publish in pending {
+        match tokio::time::timeout(Duration::from_secs(2), publish).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => panic!("Failed to publish: {}", e),
+            Err(_) => panic!("Publish request not handled"),
+        }
+    }
+}
+
+// Testing that a transaction bridged from DOG to gossipsub and back does not loop
+//     0 <---> 1, both nodes running DOG, gossipsub and a bridge
+#[tokio::test]
+pub async fn gossipsub_bridge_loop() {
+    let config = libp2p_dog::ConfigBuilder::default().build().unwrap();
+    let topic = libp2p::gossipsub::IdentTopic::new("bridge");
+
+    let base_port = rand::thread_rng().gen_range(21000..50000);
+    let addrs = [base_port, base_port + 1]
+        .map(|port| Multiaddr::from_str(&format!("/ip4/127.0.0.1/tcp/{}", port)).unwrap());
+
+    let mut node_0 = BridgeNode::spawn(
+        addrs[0].clone(),
+        vec![addrs[1].clone()],
+        config.clone(),
+        topic.clone(),
+    );
+    let mut node_1 = BridgeNode::spawn(addrs[1].clone(), vec![], config, topic);
+
+    // Wait for the swarms to connect and the subscriptions to propagate
+    sleep(Duration::from_secs(5)).await;
+
+    node_0.publish(b"Hello from node 0!".to_vec());
+
+    sleep(Duration::from_secs(5)).await;
+
+    let (transactions_0, messages_0) = node_0.collect_events();
+    let (transactions_1, messages_1) = node_1.collect_events();
+
+    // Node 1 bridges the transaction to gossipsub, and node 0 bridges the message back to DOG,
+    // where it is not bridged again.
+    assert!(transactions_0.is_empty());
+    assert_eq!(messages_0.len(), 1);
+    assert_eq!(messages_0[0].source, Some(node_1.peer_id()));
+    assert!(messages_1.is_empty());
+
+    assert_eq!(transactions_1.len(), 2);
+    assert_eq!(transactions_1[0].from, Some(node_0.peer_id()));
+    assert!(GossipsubBridge::bridged_message_id(&transactions_1[0]).is_none());
+
+    let bridged = GossipsubBridge::original_transaction(&transactions_1[1]);
+    assert!(GossipsubBridge::bridged_message_id(&transactions_1[1]).is_some());
+    assert_eq!(bridged.from, messages_0[0].source);
+    assert_eq!(bridged.seqno, messages_0[0].sequence_number);
+    assert_eq!(bridged.data, b"Hello from node 0!".to_vec());
+    assert!(bridged.headers.is_empty());
+}
+
+// Testing that a transaction replayed after it expired from the duplicate cache is rejected when
+// replay protection is enabled
+//     1 <---> 0 <---> 2, with replay protection on node 1 only
+#[tokio::test]
+pub async fn replay_after_cache_time() {
+    let config = libp2p_dog::ConfigBuilder::default().build().unwrap();
+    let config_replay = libp2p_dog::ConfigBuilder::default()
+        .cache_time(Duration::from_secs(1))
+        .replay_window(64)
+        .build()
+        .unwrap();
+    let config_no_replay = libp2p_dog::ConfigBuilder::default()
+        .cache_time(Duration::from_secs(1))
+        .build()
+        .unwrap();
+
+    const N: usize = 3;
+
+    let bootstrap_sets = [vec![1, 2], vec![], vec![]];
+
+    let mut test = match Test::<N>::new_with_each_config(
+        [config, config_replay.clone(), config_no_replay],
+        bootstrap_sets,
+        true,
+    ) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    test.publish_on_node(0, b"Hello from node 0!".to_vec());
+
+    sleep(Duration::from_secs(1)).await;
+
+    let events = test.collect_events();
+    assert_eq!(events[1].0.len(), 1);
+    assert_eq!(events[2].0.len(), 1);
+    let tx_id = config_replay.transaction_id(&events[1].0[0]);
+
+    // Wait for the transaction to expire from the duplicate caches
+    sleep(Duration::from_secs(3)).await;
+
+    test.republish_on_node(0, tx_id);
+
+    sleep(Duration::from_secs(1)).await;
+
+    let events = test.collect_events();
+    assert!(events[1].0.is_empty());
+    assert_eq!(events[2].0.len(), 1);
+    assert_eq!(events[2].0[0].data, b"Hello from node 0!".to_vec());
+}
+
+// Testing that transactions are delivered exactly once with the Bloom duplicate filter
+//     0 <---> 1 <---> 2 <---> 0
+#[tokio::test]
+pub async fn bloom_filter_deduplication() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        // High redundancy so that every node keeps receiving duplicates
+        .target_redundancy(10.0)
+        .duplicate_filter_mode(libp2p_dog::DuplicateFilterMode::Bloom {
+            capacity: 1000,
+            false_positive_rate: 0.001,
+            source_window: 100,
+        })
+        .build()
+        .unwrap();
+
+    const N: usize = 3;
+
+    let bootstrap_sets = [vec![1, 2], vec![2], vec![]];
+
+    let mut test = match Test::<N>::new_with_unique_config(config, bootstrap_sets, true) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    for i in 0..10 {
+        for j in 0..N {
+            test.publish_on_node(j, format!("Hello #{} from node {}!", i, j).into_bytes());
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    sleep(Duration::from_secs(2)).await;
+
+    let peer_ids = test.peer_ids();
+    let events = test.collect_events();
+
+    for (i, (transactions, _)) in events.iter().enumerate() {
+        let mut received = transactions
+            .iter()
+            .map(|transaction| (transaction.from, transaction.data.clone()))
+            .collect::<Vec<_>>();
+        received.sort();
+
+        let mut expected = (0..10)
+            .flat_map(|j| {
+                (0..N).filter(move |k| *k != i).map(move |k| {
+                    (
+                        Some(peer_ids[k]),
+                        format!("Hello #{} from node {}!", j, k).into_bytes(),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        expected.sort();
+
+        assert_eq!(received, expected);
+    }
+}
+
+// Testing that the maximum number of disabled routes to a peer is enforced along with the minimum
+// number of active routes, in a fully connected network where each node has four routes to each of
+// its peers
+//     0 <---> 1 <---> 2 <---> 3 <---> 4, all connected to each other
+#[tokio::test]
+pub async fn max_disabled_routes_with_min_active_routes() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        // We force the nodes to remove any redundancy
+        .target_redundancy(0.0)
+        .redundancy_delta_percent(0)
+        // The minimum number of active routes alone would allow three disabled routes per peer
+        .min_active_routes_per_peer(1)
+        .max_disabled_routes_per_peer(2)
+        // Disable signature to speed up the test
+        .validation_mode(libp2p_dog::ValidationMode::None)
+        .build()
+        .unwrap();
+
+    const N: usize = 5;
+
+    let bootstrap_sets: [Vec<usize>; N] = std::array::from_fn(|i| (i + 1..N).collect());
+
+    let mut test = match Test::<N>::new_with_unique_config(config, bootstrap_sets, false) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    for i in 0..20 {
+        for j in 0..N {
+            test.publish_on_node(j, format!("Hello #{} from node {}!", i, j).into_bytes());
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    sleep(Duration::from_secs(5)).await;
+
+    let peer_ids = test.peer_ids();
+    let events = test.collect_events();
+
+    let mut disabled = 0;
+    for (transactions, routing_updates) in events.iter() {
+        assert_eq!(transactions.len(), (N - 1) * 20);
+
+        for routes in routing_updates {
+            for peer_id in peer_ids.iter() {
+                let disabled_to_peer = routes
+                    .iter()
+                    .filter(|route| route.target() == peer_id)
+                    .count();
+                assert!(disabled_to_peer <= 2);
+                assert!(N - 1 - disabled_to_peer >= 1);
+            }
+        }
+
+        disabled += routing_updates.last().map_or(0, |routes| routes.len());
+    }
+    assert!(disabled > 0);
+}
+
+// Testing that the publisher is told which peers each of its transactions has been sent to
+//     1 <---> 0 <---> 2
+#[tokio::test]
+pub async fn publish_outcome() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        // No redundancy to avoid the nodes sending reset route messages
+        .target_redundancy(0.0)
+        .redundancy_delta_percent(0)
+        .build()
+        .unwrap();
+
+    const N: usize = 3;
+
+    let bootstrap_sets = [vec![1, 2], vec![], vec![]];
+
+    let mut test = match Test::<N>::new_with_unique_config(config, bootstrap_sets, true) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    for i in 0..10 {
+        test.publish_on_node(0, format!("Hello #{} from node 0!", i).into_bytes());
+    }
+
+    sleep(Duration::from_secs(2)).await;
+
+    let peer_ids = test.peer_ids();
+    let events = test.collect_dog_events();
+
+    let (events, errors) = &events[0];
+    assert!(errors.is_empty(), "errors: {:?}", errors);
+
+    let mut transaction_ids = HashSet::new();
+    for event in events {
+        if let libp2p_dog::Event::PublishOutcome {
+            transaction_id,
+            sent_to,
+            dropped_at,
+        } = event
+        {
+            assert!(transaction_ids.insert(transaction_id.clone()));
+
+            let sent_to = sent_to.iter().cloned().collect::<HashSet<_>>();
+            assert_eq!(sent_to, HashSet::from([peer_ids[1], peer_ids[2]]));
+            assert!(dropped_at.is_empty());
+        }
+    }
+    assert_eq!(transaction_ids.len(), 10);
+}
+
+// Testing that the publisher is told which peers its transactions could not be sent to, here a
+// peer that does not support the DOG protocol
+//     0 <---> 1 (no DOG)
+#[tokio::test]
+pub async fn publish_outcome_dropped() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        .connection_handler_publish_duration(Duration::from_secs(1))
+        .build()
+        .unwrap();
+
+    let mut test = match Test::<1>::new_with_unique_config(config, [vec![]], true) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    let dummy_node = DummyNode::spawn(test.addrs().to_vec());
+
+    sleep(Duration::from_secs(1)).await;
+
+    for i in 0..10 {
+        test.publish_on_node(0, format!("Hello #{} from node 0!", i).into_bytes());
+    }
+
+    // The transactions are considered dropped once they have waited for twice the publish queue
+    // duration
+    sleep(Duration::from_secs(4)).await;
+
+    let events = test.collect_dog_events();
+
+    let (events, errors) = &events[0];
+    assert!(errors.is_empty(), "errors: {:?}", errors);
+
+    let outcomes = events
+        .iter()
+        .filter_map(|event| match event {
+            libp2p_dog::Event::PublishOutcome {
+                sent_to,
+                dropped_at,
+                ..
+            } => Some((sent_to, dropped_at)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(outcomes.len(), 10);
+    for (sent_to, dropped_at) in outcomes {
+        assert!(sent_to.is_empty());
+        assert_eq!(dropped_at, &vec![dummy_node.peer_id()]);
+    }
+}
+
+// Testing that a republished transaction reaches the peers that joined since it was published,
+// with the same sequence number, and is not delivered again to the others
+//     1 <---> 0 <---> 2 (joins later)
+#[tokio::test]
+pub async fn republish_to_late_joiner() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        // No redundancy to avoid the nodes sending reset route messages
+        .target_redundancy(0.0)
+        .redundancy_delta_percent(0)
+        .build()
+        .unwrap();
+
+    const N: usize = 3;
+
+    let bootstrap_sets = [vec![], vec![0], vec![0]];
+
+    let mut test = match Test::<N>::new_with_unique_config(config.clone(), bootstrap_sets, true) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_node(0).await;
+    test.spawn_node(1).await;
+
+    test.publish_on_node(0, b"Hello from node 0!".to_vec());
+
+    sleep(Duration::from_secs(1)).await;
+
+    let events = test.collect_events();
+    assert_eq!(events[1].0.len(), 1);
+    let published = events[1].0[0].clone();
+
+    test.spawn_node(2).await;
+
+    test.republish_on_node(0, config.transaction_id(&published));
+
+    sleep(Duration::from_secs(1)).await;
+
+    let events = test.collect_events();
+    assert!(events[1].0.is_empty());
+    assert_eq!(events[2].0.len(), 1);
+
+    let republished = &events[2].0[0];
+    assert_eq!(republished.from, published.from);
+    assert_eq!(republished.seqno, published.seqno);
+    assert_eq!(republished.data, published.data);
+}
+
+// Testing that the transactions published before any DOG peer is connected are buffered and sent
+// to the first one, and not to a peer that does not support the DOG protocol
+//     2 (no DOG) <---> 0 <---> 1 (joins later)
+#[tokio::test]
+pub async fn pending_publish_buffer() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        // No redundancy to avoid the nodes sending reset route messages
+        .target_redundancy(0.0)
+        .redundancy_delta_percent(0)
+        .max_pending_publishes(10)
+        .build()
+        .unwrap();
+
+    let bootstrap_sets = [vec![], vec![0]];
+
+    let mut test = match Test::<2>::new_with_unique_config(config, bootstrap_sets, true) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_node(0).await;
+
+    let _dummy_node = DummyNode::spawn(vec![test.addrs()[0].clone()]);
+
+    sleep(Duration::from_secs(1)).await;
+
+    for i in 0..5 {
+        test.publish_on_node(0, format!("Hello #{} from node 0!", i).into_bytes());
+    }
+
+    sleep(Duration::from_secs(1)).await;
+
+    test.spawn_node(1).await;
+
+    let peer_ids = test.peer_ids();
+    let events = test.collect_events();
+
+    assert_eq!(events[1].0.len(), 5);
+    for (i, transaction) in events[1].0.iter().enumerate() {
+        assert_eq!(transaction.from, Some(peer_ids[0]));
+        assert_eq!(
+            transaction.data,
+            format!("Hello #{} from node 0!", i).into_bytes()
+        );
+    }
+}
+
+// Testing that the buffered transactions are reported when they are evicted from the full buffer
+// or expire
+//     0
+#[tokio::test]
+pub async fn pending_publish_expired() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        .max_pending_publishes(2)
+        .pending_publish_ttl(Duration::from_secs(1))
+        .build()
+        .unwrap();
+
+    let mut test = match Test::<1>::new_with_unique_config(config, [vec![]], true) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    for i in 0..3 {
+        test.publish_on_node(0, format!("Hello #{} from node 0!", i).into_bytes());
+    }
+
+    sleep(Duration::from_millis(100)).await;
+
+    let events = test.collect_dog_events();
+    let (events, errors) = &events[0];
+    assert!(errors.is_empty(), "errors: {:?}", errors);
+    let expired = events
+        .iter()
+        .filter(|event| matches!(event, libp2p_dog::Event::PublishExpired { .. }))
+        .count();
+    assert_eq!(expired, 1);
+
+    sleep(Duration::from_secs(3)).await;
+
+    let events = test.collect_dog_events();
+    let (events, errors) = &events[0];
+    assert!(errors.is_empty(), "errors: {:?}", errors);
+    let expired = events
+        .iter()
+        .filter(|event| matches!(event, libp2p_dog::Event::PublishExpired { .. }))
+        .count();
+    assert_eq!(expired, 2);
+}
+
+// Testing that a transaction that could not be published because no peer was connected can be
+// published again, even with content-addressed ids
+//     0 <---> 1 (joins later)
+#[tokio::test]
+pub async fn publish_again_without_peers() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        .transaction_id_mode(libp2p_dog::TransactionIdMode::Sha256)
+        .build()
+        .unwrap();
+
+    let bootstrap_sets = [vec![], vec![0]];
+
+    let mut test = match Test::<2>::new_with_unique_config(config, bootstrap_sets, true) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_node(0).await;
+
+    test.publish_on_node(0, b"Hello from node 0!".to_vec());
+
+    sleep(Duration::from_millis(100)).await;
+
+    let events = test.collect_dog_events();
+    assert_eq!(
+        events[0].1,
+        vec!["Failed to publish data: InsufficientPeers".to_string()]
+    );
+
+    test.spawn_node(1).await;
+
+    test.publish_on_node(0, b"Hello from node 0!".to_vec());
+
+    sleep(Duration::from_secs(1)).await;
+
+    let events = test.collect_events();
+    assert_eq!(events[1].0.len(), 1);
+    assert_eq!(events[1].0[0].data, b"Hello from node 0!".to_vec());
+}
+
+// Testing that a transaction whose publication fails because all the queues are full is neither
+// delivered locally nor sent later, and can be published again
+//     0 <---> 1
+#[tokio::test]
+pub async fn publish_again_with_full_queues() {
+    const N: usize = 100;
+
+    let config = libp2p_dog::ConfigBuilder::default()
+        .transaction_id_mode(libp2p_dog::TransactionIdMode::Sha256)
+        // A single publish can be queued at a time
+        .connection_handler_queue_len(2)
+        .deliver_own_transactions(true)
+        .publish_max_retries(3)
+        .build()
+        .unwrap();
+
+    let bootstrap_sets = [vec![], vec![0]];
+
+    let mut test = match Test::<2>::new_with_unique_config(config, bootstrap_sets, true) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    for i in 0..N {
+        test.publish_on_node(0, format!("Hello #{} from node 0!", i).into_bytes());
+    }
+
+    sleep(Duration::from_secs(2)).await;
+
+    let events = test.collect_dog_events();
+    let delivered = |events: &[libp2p_dog::Event]| {
+        let mut data = events
+            .iter()
+            .filter_map(|event| match event {
+                libp2p_dog::Event::Transaction { transaction, .. } => {
+                    Some(transaction.data.clone())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        data.sort();
+        data
+    };
+
+    let failed = events[0].1.len();
+    assert!(failed > 0);
+    assert!(events[0]
+        .1
+        .iter()
+        .all(|error| error == "Failed to publish data: AllQueuesFull(1)"));
+
+    // Only the transactions that were published are delivered locally and sent
+    let published = delivered(&events[0].0);
+    assert_eq!(published.len(), N - failed);
+    assert_eq!(delivered(&events[1].0), published);
+
+    // The failed transactions are not duplicates
+    for i in 0..N {
+        let data = format!("Hello #{} from node 0!", i).into_bytes();
+        if published.binary_search(&data).is_err() {
+            test.publish_on_node(0, data);
+            sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    sleep(Duration::from_secs(1)).await;
+
+    let events = test.collect_dog_events();
+    assert_eq!(events[0].1, Vec::<String>::new());
+    assert_eq!(delivered(&events[1].0).len(), failed);
+}
+
+// Testing that the transaction store keeps the most recent transactions within its bounds, in
+// number of transactions on node 1 and in bytes on node 2, which limits what late joiners sync
+//     3 (joins later) <---> 1 <---> 0 <---> 2 <---> 4 (joins later)
+#[tokio::test]
+pub async fn store_bounds() {
+    const DATA_LEN: usize = "Hello #0 from node 0!".len();
+
+    let config = libp2p_dog::ConfigBuilder::default()
+        // No redundancy to avoid the nodes sending reset route messages
+        .target_redundancy(0.0)
+        .redundancy_delta_percent(0)
+        .sync_on_connection(true)
+        .build()
+        .unwrap();
+    let config_count = libp2p_dog::ConfigBuilder::from(config.clone())
+        .max_stored_transactions(5)
+        .build()
+        .unwrap();
+    let config_bytes = libp2p_dog::ConfigBuilder::from(config.clone())
+        .max_stored_transactions(100)
+        // The data is stored both as received and as delivered
+        .max_stored_bytes(3 * 2 * DATA_LEN)
+        .build()
+        .unwrap();
+
+    const N: usize = 5;
+
+    let bootstrap_sets = [vec![], vec![0], vec![0], vec![1], vec![2]];
+
+    let mut test = match Test::<N>::new_with_each_config(
+        [
+            config.clone(),
+            config_count,
+            config_bytes,
+            config.clone(),
+            config,
+        ],
+        bootstrap_sets,
+        true,
+    ) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_node(0).await;
+    test.spawn_node(1).await;
+    test.spawn_node(2).await;
+
+    for i in 0..10 {
+        test.publish_on_node(0, format!("Hello #{} from node 0!", i).into_bytes());
+    }
+
+    sleep(Duration::from_secs(2)).await;
+
+    test.spawn_node(3).await;
+    test.spawn_node(4).await;
+
+    let events = test.collect_events();
+
+    for (node, stored) in [(3, 5), (4, 3)] {
+        let mut data = events[node]
+            .0
+            .iter()
+            .map(|transaction| transaction.data.clone())
+            .collect::<Vec<_>>();
+        data.sort();
+
+        let expected = (10 - stored..10)
+            .map(|i| format!("Hello #{} from node 0!", i).into_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(data, expected);
+    }
+}
+
+// Testing that the duplicate cache evicts the oldest ids when it reaches its capacity, so that a
+// transaction published again after its id was evicted is delivered again
+//     1 <---> 0 <---> 2, with a cache of a single id on nodes 0 and 1
+#[tokio::test]
+pub async fn cache_capacity() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        // No redundancy to avoid the nodes sending reset route messages
+        .target_redundancy(0.0)
+        .redundancy_delta_percent(0)
+        // Content-addressed ids, so that publishing the same data gives the same id
+        .transaction_id_mode(libp2p_dog::TransactionIdMode::Sha256)
+        .build()
+        .unwrap();
+    let config_single = libp2p_dog::ConfigBuilder::from(config.clone())
+        .cache_capacity(1)
+        .build()
+        .unwrap();
+
+    const N: usize = 3;
+
+    let bootstrap_sets = [vec![1, 2], vec![], vec![]];
+
+    let mut test = match Test::<N>::new_with_each_config(
+        [config_single.clone(), config_single, config],
+        bootstrap_sets,
+        true,
+    ) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    for data in [
+        "Hello from node 0!",
+        "Bye from node 0!",
+        "Hello from node 0!",
+    ] {
+        test.publish_on_node(0, data.as_bytes().to_vec());
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    sleep(Duration::from_secs(1)).await;
+
+    let events = test.collect_events();
+
+    let received = |node: usize| {
+        events[node]
+            .0
+            .iter()
+            .map(|transaction| String::from_utf8(transaction.data.clone()).unwrap())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        received(1),
+        vec![
+            "Hello from node 0!",
+            "Bye from node 0!",
+            "Hello from node 0!"
+        ]
+    );
+    assert_eq!(received(2), vec!["Hello from node 0!", "Bye from node 0!"]);
+}
+
+// Testing that the routes are not re-enabled beyond the maximum number of ResetRoute messages
+// honoured per interval, here none, in the network of the reset route scenario
+//     A <---> Bi <---> C
+#[tokio::test]
+pub async fn reset_route_interval_cap() {
+    const R: usize = 3;
+    const B: usize = 2 * R + 1; // Number of Bi nodes
+    const N: usize = B + 2; // Total number of nodes
+
+    let config_a_bi = libp2p_dog::ConfigBuilder::default()
+        // We force the nodes to remove any redundancy
+        .target_redundancy(0.0)
+        .redundancy_delta_percent(0)
+        .redundancy_interval(Duration::from_secs(100))
+        // No ResetRoute message is honoured
+        .max_reset_routes_per_interval(0)
+        // Disable signature to speed up the test
+        .validation_mode(libp2p_dog::ValidationMode::None)
+        .build()
+        .unwrap();
+    let config_c = libp2p_dog::ConfigBuilder::default()
+        .target_redundancy(R as f64)
+        .redundancy_delta_percent(0)
+        // Speed up have_tx unblocking
+        .redundancy_interval(Duration::from_millis(10))
+        // Disable signature to speed up the test
+        .validation_mode(libp2p_dog::ValidationMode::None)
+        // For simplicity, node C acts as a client
+        .forward_transactions(false)
+        .build()
+        .unwrap();
+
+    let configs: [libp2p_dog::Config; N] = std::array::from_fn(|i| {
+        if i == N - 1 {
+            config_c.clone()
+        } else {
+            config_a_bi.clone()
+        }
+    });
+    let bootstrap_sets: [Vec<usize>; N] = std::array::from_fn(|i| match i {
+        0 => (1..B + 1).collect(),
+        i if i == N - 1 => vec![],
+        _ => vec![N - 1],
+    });
+
+    let mut test = match Test::<N>::new_with_each_config(configs, bootstrap_sets, false) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    for i in 0..B - R + 1 {
+        test.publish_on_node(0, format!("Hello #{} from node A!", i).into_bytes());
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    sleep(Duration::from_secs(5)).await;
+
+    let events = test.collect_events();
+
+    // The number of routes disabled by each Bi node
+    let disabled_routes: Vec<usize> = (1..B + 1)
+        .map(|i| events[i].1.last().map_or(0, |routes| routes.len()))
+        .collect();
+    assert!(disabled_routes.iter().any(|disabled| *disabled > 0));
+
+    // Kill the Bi nodes that have not disabled their route to C but one, so that C keeps receiving
+    // the transactions without redundancy
+    let mut bi_nodes_to_kill = (1..B + 1)
+        .filter(|i| disabled_routes[i - 1] == 0)
+        .collect::<Vec<_>>();
+    bi_nodes_to_kill.shuffle(&mut rand::thread_rng());
+    bi_nodes_to_kill.pop();
+
+    for bi_node in bi_nodes_to_kill.iter() {
+        test.kill_node(*bi_node).await;
+    }
+
+    for i in 0..R {
+        test.publish_on_node(0, format!("Hello again #{} from node A!", i).into_bytes());
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    sleep(Duration::from_secs(5)).await;
+
+    let events = test.collect_events();
+
+    assert_eq!(events[N - 1].0.len(), R);
+
+    // C requests its routes to be re-enabled, but the Bi nodes keep them disabled
+    for i in (1..B + 1).filter(|i| !bi_nodes_to_kill.contains(i)) {
+        let disabled = events[i]
+            .1
+            .last()
+            .map_or(disabled_routes[i - 1], |routes| routes.len());
+        assert_eq!(disabled, disabled_routes[i - 1]);
+    }
+}
+
+// Testing that the HaveTx messages of honest peers refer to transactions sent to them, so that they
+// disable routes and are never reported as a misbehaviour, in a fully connected network
+//     0 <---> 1 <---> 2 <---> 3, all connected to each other
+#[tokio::test]
+pub async fn have_tx_from_honest_peers() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        // We force the nodes to remove any redundancy
+        .target_redundancy(0.0)
+        .redundancy_delta_percent(0)
+        // Disable signature to speed up the test
+        .validation_mode(libp2p_dog::ValidationMode::None)
+        .build()
+        .unwrap();
+
+    const N: usize = 4;
+
+    let bootstrap_sets: [Vec<usize>; N] = std::array::from_fn(|i| (i + 1..N).collect());
+
+    let mut test = match Test::<N>::new_with_unique_config(config, bootstrap_sets, false) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    for i in 0..20 {
+        for j in 0..N {
+            test.publish_on_node(j, format!("Hello #{} from node {}!", i, j).into_bytes());
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    sleep(Duration::from_secs(5)).await;
+
+    let events = test.collect_dog_events();
+
+    let mut disabled = 0;
+    for (events, errors) in events.iter() {
+        assert!(errors.is_empty(), "errors: {:?}", errors);
+
+        let mut transactions = 0;
+        let mut disabled_routes = 0;
+        for event in events {
+            match event {
+                libp2p_dog::Event::Transaction { .. } => transactions += 1,
+                libp2p_dog::Event::RoutingUpdated {
+                    disabled_routes: routes,
+                } => disabled_routes = routes.len(),
+                libp2p_dog::Event::PeerMisbehaviour { misbehaviour, .. } => {
+                    panic!("Unexpected misbehaviour: {:?}", misbehaviour)
+                }
+                _ => {}
+            }
+        }
+
+        assert_eq!(transactions, (N - 1) * 20);
+        disabled += disabled_routes;
+    }
+    assert!(disabled > 0);
+}
+
+// Testing that a HaveTx for a transaction never sent to the peer is ignored and reported as a
+// misbehaviour
+//     1 ---> 0 <--- raw node
+#[tokio::test]
+pub async fn unsolicited_have_tx() {
+    let config = libp2p_dog::ConfigBuilder::default()
+        // Node 0 does not forward the transactions of node 1 to the raw node
+        .forward_transactions(false)
+        .build()
+        .unwrap();
+
+    let bootstrap_sets = [vec![], vec![0]];
+
+    let mut test = match Test::<2>::new_with_unique_config(config, bootstrap_sets, true) {
+        Ok(test) => test,
+        Err(e) => panic!("Failed to create test: {}", e),
+    };
+
+    test.spawn_all().await;
+
+    let raw_node = RawNode::spawn(vec![test.addrs()[0].clone()]);
+
+    test.publish_on_node(1, b"Hello from node 1!".to_vec());
+
+    sleep(Duration::from_secs(1)).await;
+
+    let events = test.collect_dog_events();
+    let tx_id = events[0]
+        .0
+        .iter()
+        .find_map(|event| match event {
+            libp2p_dog::Event::Transaction { transaction_id, .. } => Some(transaction_id.clone()),
+            _ => None,
+        })
+        .expect("Node 0 should have received the transaction");
+
+    // The transaction was received from node 1, so a solicited HaveTx would disable the route
+    // from node 1 to the raw node.
+    raw_node.send_have_tx(&tx_id);
+
+    sleep(Duration::from_secs(1)).await;
+
+    let events = test.collect_dog_events();
+
+    let mut misbehaviours = 0;
+    for event in &events[0].0 {
+        match event {
+            libp2p_dog::Event::RoutingUpdated { disabled_routes } => {
+                assert!(disabled_routes.is_empty());
+            }
+            libp2p_dog::Event::PeerMisbehaviour {
+                peer_id,
+                misbehaviour,
+            } => {
+                assert_eq!(*peer_id, raw_node.peer_id());
+                assert!(matches!(
+                    misbehaviour,
+                    libp2p_dog::Misbehaviour::UnsolicitedHaveTx { transactions: 1 }
+                ));
+                misbehaviours += 1;
+            }
+            _ => {}
+        }
+    }
+    assert_eq!(misbehaviours, 1);
+}
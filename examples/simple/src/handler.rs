@@ -31,6 +31,35 @@ async fn handle_dog_event(
         libp2p_dog::Event::RoutingUpdated { disabled_routes } => {
             info!("Updated routing table: {:?}", disabled_routes);
         }
+        libp2p_dog::Event::PublishOutcome {
+            transaction_id,
+            sent_to,
+            dropped_at,
+        } => {
+            info!(
+                "Transaction {} sent to {:?}, dropped at {:?}",
+                transaction_id, sent_to, dropped_at
+            );
+        }
+        libp2p_dog::Event::PublishExpired { transaction_id } => {
+            info!("Transaction {} expired before being sent", transaction_id);
+        }
+        libp2p_dog::Event::PeerRateLimited {
+            peer_id,
+            dropped_transactions,
+            dropped_control_messages,
+        } => {
+            info!(
+                "Peer {} rate limited: dropped {} transactions and {} control messages",
+                peer_id, dropped_transactions, dropped_control_messages
+            );
+        }
+        libp2p_dog::Event::PeerMisbehaviour {
+            peer_id,
+            misbehaviour,
+        } => {
+            warn!("Peer {} misbehaved: {:?}", peer_id, misbehaviour);
+        }
     }
 }
 